@@ -0,0 +1,254 @@
+use crate::cli::BatchArgs;
+use crate::constants::TICK_DURATION;
+use crate::export::{self, RunSummary};
+use crate::headless;
+use crate::scenario::Scenario;
+use crate::simulation::Simulation;
+use crate::stats::Estimate;
+use serde::Serialize;
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// One scenario run with one policy, over every seed.
+struct Group {
+    scenario_name: String,
+    scenario: Scenario,
+}
+
+/// Results of one group, aggregated over its seeds, flattened to a single row.
+/// Every `*_ci95` is the half-width of the 95% confidence interval of the
+/// mean, empty with fewer than two runs.
+#[derive(Debug, Clone, Serialize)]
+struct Comparison {
+    scenario: String,
+    policy: String,
+    runs: u32,
+    /// Cars that left the map per minute.
+    throughput_mean: Option<f32>,
+    throughput_ci95: Option<f32>,
+    /// Mean trip time of a run, over the runs where any car completed one.
+    pass_time_mean: Option<f32>,
+    pass_time_ci95: Option<f32>,
+    close_calls_mean: Option<f32>,
+    close_calls_ci95: Option<f32>,
+    collisions_mean: Option<f32>,
+    collisions_ci95: Option<f32>,
+}
+
+impl Comparison {
+    fn new(scenario: &str, policy: &str, ticks: u64, runs: &[RunSummary]) -> Self {
+        let minutes = ticks as f32 * TICK_DURATION / 60.0;
+        let throughput = Estimate::of(
+            &runs
+                .iter()
+                .map(|run| run.cars_completed as f32 / minutes)
+                .collect::<Vec<_>>(),
+        );
+        let pass_time = Estimate::of(
+            &runs
+                .iter()
+                .filter_map(|run| run.trip_time_mean)
+                .collect::<Vec<_>>(),
+        );
+        let close_calls = Estimate::of(
+            &runs
+                .iter()
+                .map(|run| run.close_calls as f32)
+                .collect::<Vec<_>>(),
+        );
+        let collisions = Estimate::of(
+            &runs
+                .iter()
+                .map(|run| run.collisions as f32)
+                .collect::<Vec<_>>(),
+        );
+
+        Self {
+            scenario: scenario.to_string(),
+            policy: policy.to_string(),
+            runs: runs.len() as u32,
+            throughput_mean: throughput.map(|e| e.mean),
+            throughput_ci95: throughput.and_then(|e| e.ci95),
+            pass_time_mean: pass_time.map(|e| e.mean),
+            pass_time_ci95: pass_time.and_then(|e| e.ci95),
+            close_calls_mean: close_calls.map(|e| e.mean),
+            close_calls_ci95: close_calls.and_then(|e| e.ci95),
+            collisions_mean: collisions.map(|e| e.mean),
+            collisions_ci95: collisions.and_then(|e| e.ci95),
+        }
+    }
+}
+
+/// The `batch` subcommand: every seed of every scenario with every policy,
+/// spread over a pool of threads, then compared.
+pub fn batch(args: &BatchArgs) -> Result<(), String> {
+    // Scenarios are named after their file for the output directories,
+    // numbered when two files share a name
+    let mut scenarios: Vec<(String, Scenario)> = Vec::new();
+    for path in &args.scenarios {
+        let stem = path
+            .file_stem()
+            .map_or("scenario".into(), |stem| stem.to_string_lossy());
+        let mut name = stem.to_string();
+        for number in 2.. {
+            if !scenarios.iter().any(|(taken, _)| *taken == name) {
+                break;
+            }
+            name = format!("{stem}-{number}");
+        }
+        scenarios.push((name, Scenario::load(path)?));
+    }
+    if scenarios.is_empty() {
+        scenarios.push(("default".to_string(), Scenario::default()));
+    }
+
+    let names: Vec<String> = scenarios.iter().map(|(name, _)| name.clone()).collect();
+    let mut groups = Vec::new();
+    for (name, mut scenario) in scenarios {
+        scenario.override_with(None, args.window_size);
+        scenario
+            .validate()
+            .map_err(|e| format!("Invalid scenario {name}: {e}"))?;
+        if args.policies.is_empty() {
+            groups.push(Group {
+                scenario_name: name,
+                scenario,
+            });
+            continue;
+        }
+        for &policy in &args.policies {
+            let mut scenario = scenario.clone();
+            scenario.override_with(Some(policy), None);
+            groups.push(Group {
+                scenario_name: name.clone(),
+                scenario,
+            });
+        }
+    }
+
+    // Every (group, seed) pair is a job; workers take the next one until
+    // none are left. Results land in job order whatever thread ran them.
+    let jobs: Vec<(usize, u64)> = (0..groups.len())
+        .flat_map(|group| (args.seed..args.seed + args.runs).map(move |seed| (group, seed)))
+        .collect();
+    let threads = args
+        .threads
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()))
+        .clamp(1, jobs.len().max(1));
+    println!("Running {} runs on {threads} threads", jobs.len());
+
+    let next_job = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<Result<RunSummary, String>>>> =
+        Mutex::new(vec![None; jobs.len()]);
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                let index = next_job.fetch_add(1, Ordering::Relaxed);
+                let Some(&(group, seed)) = jobs.get(index) else {
+                    break;
+                };
+                let result = run_one(args, &groups[group], seed);
+                results.lock().unwrap()[index] = Some(result);
+            });
+        }
+    });
+
+    let mut summaries = Vec::new();
+    for result in results.into_inner().unwrap() {
+        summaries.push(result.expect("every job is run")?);
+    }
+
+    // Each group's runs, in the order of `groups`
+    let group_runs: Vec<&[RunSummary]> = summaries.chunks(args.runs.max(1) as usize).collect();
+    let comparisons: Vec<Comparison> = groups
+        .iter()
+        .zip(&group_runs)
+        .map(|(group, runs)| {
+            let policy = runs.first().map_or("", |run| run.policy.as_str());
+            Comparison::new(&group.scenario_name, policy, args.ticks, runs)
+        })
+        .collect();
+    let table = markdown(args, &comparisons);
+    println!();
+    print!("{table}");
+
+    if let Some(output) = &args.output {
+        fs::create_dir_all(output)
+            .map_err(|e| format!("Failed to create {}: {e}", output.display()))?;
+
+        // One summary per scenario, with a row per policy and seed
+        for name in &names {
+            let rows: Vec<RunSummary> = groups
+                .iter()
+                .zip(&group_runs)
+                .filter(|(group, _)| &group.scenario_name == name)
+                .flat_map(|(_, runs)| runs.iter().cloned())
+                .collect();
+            export::export_summaries(&output.join(name), args.format, &rows)?;
+        }
+
+        export::write_table(output, "comparison", args.format, &comparisons)?;
+        let path = output.join("comparison.md");
+        fs::write(&path, &table).map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+        println!("Exported batch to {}", output.display());
+    }
+    Ok(())
+}
+
+/// Simulate one seed of `group`, exporting it under `--output` if given.
+fn run_one(args: &BatchArgs, group: &Group, seed: u64) -> Result<RunSummary, String> {
+    let mut simulation = Simulation::new(seed, group.scenario.clone());
+    headless::simulate(&mut simulation, args.ticks);
+
+    let name = &group.scenario_name;
+    let policy = simulation.policy_name();
+    let metrics = simulation.metrics();
+    println!(
+        "{name} seed {seed} ({policy}): {} arrived, {} spawned, {} turned away, {} completed, {} collisions, {} close calls",
+        metrics.cars_arrived,
+        metrics.cars_spawned,
+        metrics.spawns_rejected,
+        metrics.trips.len(),
+        metrics.collision_count,
+        metrics.close_call_count,
+    );
+    if let Some(output) = &args.output {
+        let directory = output.join(name).join(policy).join(format!("seed-{seed}"));
+        export::export_run(&directory, args.format, seed, policy, metrics)?;
+    }
+    Ok(RunSummary::new(seed, policy, metrics))
+}
+
+/// The comparison as a Markdown table, each cell the mean ± its 95%
+/// confidence interval.
+fn markdown(args: &BatchArgs, comparisons: &[Comparison]) -> String {
+    let cell = |mean: Option<f32>, ci95: Option<f32>| match (mean, ci95) {
+        (Some(mean), Some(ci95)) => format!("{mean:.2} ± {ci95:.2}"),
+        (Some(mean), None) => format!("{mean:.2}"),
+        _ => "–".to_string(),
+    };
+
+    let mut table = format!(
+        "{} seeds from {}, {} ticks each; mean ± 95% confidence interval over the seeds.\n\n",
+        args.runs, args.seed, args.ticks
+    );
+    table.push_str(
+        "| Scenario | Policy | Runs | Throughput (cars/min) | Pass time (s) | Close calls | Collisions |\n",
+    );
+    table.push_str("|---|---|---:|---:|---:|---:|---:|\n");
+    for row in comparisons {
+        table.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} | {} |\n",
+            row.scenario,
+            row.policy,
+            row.runs,
+            cell(row.throughput_mean, row.throughput_ci95),
+            cell(row.pass_time_mean, row.pass_time_ci95),
+            cell(row.close_calls_mean, row.close_calls_ci95),
+            cell(row.collisions_mean, row.collisions_ci95),
+        ));
+    }
+    table
+}
@@ -1,58 +1,12 @@
-use crate::{constants::*, increment_spawn_count};
+use crate::geometry::{FRect, OrientedRect, Vec2};
+use crate::idm::IdmParams;
+use crate::path::{Path, Pose};
+use crate::route::{Direction, Route};
+use crate::scenario::Scenario;
 use rand::Rng;
-use sdl2::rect::Rect as SdlRect;
+use sdl2::rect::{Point as SdlPoint, Rect as SdlRect};
 use sdl2::render::BlendMode;
 use sdl2::render::{Texture, WindowCanvas};
-use std::time::Instant;
-
-/// A simple 2D vector for float values
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub struct Vec2 {
-    pub x: f32,
-    pub y: f32,
-}
-
-impl Vec2 {
-    pub fn new(x: f32, y: f32) -> Self {
-        Self { x, y }
-    }
-    pub fn distance(&self, other: Vec2) -> f32 {
-        let dx = other.x - self.x;
-        let dy = other.y - self.y;
-        (dx * dx + dy * dy).sqrt()
-    }
-}
-
-/// A float-based rectangle for collision/drawing
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub struct FRect {
-    pub x: f32,
-    pub y: f32,
-    pub w: f32,
-    pub h: f32,
-}
-
-impl FRect {
-    pub fn new(x: f32, y: f32, w: f32, h: f32) -> Self {
-        Self { x, y, w, h }
-    }
-
-    /// Returns `Some(intersection)` if `self` intersects `other`, else `None`.
-    pub fn intersect(&self, other: FRect) -> Option<FRect> {
-        let rx = self.x.max(other.x);
-        let ry = self.y.max(other.y);
-        let rw = (self.x + self.w).min(other.x + other.w) - rx;
-        let rh = (self.y + self.h).min(other.y + other.h) - ry;
-        if rw > 0.0 && rh > 0.0 {
-            Some(FRect::new(rx, ry, rw, rh))
-        } else {
-            None
-        }
-    }
-}
-
-pub const CAR_SIZE: Vec2 = Vec2 { x: 43., y: 33. };
-pub const RADAR_SIZE: Vec2 = Vec2 { x: 43., y: 33. };
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Dimensions {
@@ -60,6 +14,21 @@ pub struct Dimensions {
     pub short_edge: f32,
     pub delta_edge: f32,
 }
+
+impl Dimensions {
+    pub fn new(long_edge: f32, short_edge: f32) -> Self {
+        Self {
+            long_edge,
+            short_edge,
+            delta_edge: long_edge - short_edge,
+        }
+    }
+
+    /// Length along the heading and width across it.
+    pub fn size(&self) -> Vec2 {
+        Vec2::new(self.long_edge, self.short_edge)
+    }
+}
 #[derive(Debug, PartialEq, Clone)]
 pub enum IntersectionState {
     Before,
@@ -70,317 +39,310 @@ pub enum IntersectionState {
 pub struct Car {
     pub id: u32, // Changed from uuid to simple integer id
     pub spawn_point: Vec2,
-    pub lifetime: Instant,
+    pub spawn_tick: u64,
+    /// Tick the car first touched the core intersection.
+    pub entry_tick: Option<u64>,
+    /// Tick the car was clear of the core intersection again.
+    pub exit_tick: Option<u64>,
+    /// Ticks spent waiting, see `trip::WAITING_SPEED`.
+    pub wait_ticks: u32,
+    /// Tick the car started its current wait, if it's waiting.
+    pub waiting_since: Option<u64>,
+    /// The car body, turned to its heading.
+    pub body: OrientedRect,
+    /// Bounding box of `body`.
     pub car_rect: FRect,
-    pub current_direction: String,
-    pub current_speed: f32,
-    pub randomized_initial_speed: f32,
-    pub radar: FRect,
+    /// Compass heading closest to `heading`.
+    pub current_direction: Direction,
+    pub current_speed: f32, // pixels per second
+    /// Change of speed over the last tick, in px/s².
+    pub acceleration: f32,
+    /// Change of acceleration over the last tick, in px/s³.
+    pub jerk: f32,
+    /// How smooth the ride has been so far.
+    pub comfort: Comfort,
+    /// How this car's driver accelerates and keeps distance.
+    pub driver: IdmParams,
+    /// Area ahead of the car that it watches for other cars.
+    pub radar: OrientedRect,
+    /// Free distance ahead of the car, up to one radar length.
     pub proximity: f32,
+    /// Speed of whatever ends the radar, along our heading,
+    /// or `None` if the radar is clear.
+    pub leader_speed: Option<f32>,
     pub has_turned: bool,
-    pub behavior_code: String,
-    pub waiting_flag: bool,
+    pub route: Route,
+    pub path: Path,
+    /// How far along `path` the center of the car is, in pixels.
+    pub distance: f32,
+    /// Heading in radians, 0 is East and PI/2 is South.
+    pub heading: f32,
     pub car_size: Dimensions,
     pub radar_size: Dimensions,
-    pub dest_point: Vec2,
     pub intersection_state: IntersectionState,
+    /// Distance left to the stop line while the policy holds this car back.
+    pub stop_line_gap: Option<f32>,
+    /// Whether the policy let this car through ahead of crossing traffic,
+    /// so its radar only watches cars on its own route.
+    pub right_of_way: bool,
+}
+
+/// Running record of what a passenger in a car has felt.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Comfort {
+    /// Hardest braking so far, in px/s², as a positive number.
+    pub peak_deceleration: f32,
+    /// Times the car braked harder than its driver is comfortable with.
+    pub hard_braking_events: u32,
+    total_abs_jerk: f32,
+    ticks: u32,
+    hard_braking: bool,
+}
+
+impl Comfort {
+    fn record(&mut self, acceleration: f32, jerk: f32, comfortable_deceleration: f32) {
+        self.peak_deceleration = self.peak_deceleration.max(-acceleration);
+        self.total_abs_jerk += jerk.abs();
+        self.ticks += 1;
+
+        // A stretch of hard braking over several ticks is one event
+        let hard_braking = -acceleration > comfortable_deceleration;
+        if hard_braking && !self.hard_braking {
+            self.hard_braking_events += 1;
+        }
+        self.hard_braking = hard_braking;
+    }
+
+    /// Mean absolute jerk over the ticks recorded, in px/s³.
+    pub fn mean_abs_jerk(&self) -> f32 {
+        if self.ticks == 0 {
+            0.0
+        } else {
+            self.total_abs_jerk / self.ticks as f32
+        }
+    }
 }
 
 /// If so, move the car with the lower index back by 5 pixels.
-pub fn check_perpendicular_and_move_back(cars: &mut Vec<Car>, i: usize, j: usize) {
+pub fn check_perpendicular_and_move_back(cars: &mut [Car], i: usize, j: usize) {
     let car1 = &cars[i];
     let car2 = &cars[j];
 
     // Check if the cars are perpendicular
-    let are_perpendicular = (car1.current_direction == "West" || car1.current_direction == "East")
-        && (car2.current_direction == "North" || car2.current_direction == "South")
-        || (car1.current_direction == "North" || car1.current_direction == "South")
-            && (car2.current_direction == "West" || car2.current_direction == "East");
+    let are_perpendicular = car1
+        .current_direction
+        .is_perpendicular_to(car2.current_direction);
 
     // Check if both cars have a speed of 0
     let both_stopped = car1.current_speed == 0.0 && car2.current_speed == 0.0;
 
     if are_perpendicular && both_stopped {
-        // Move the car with the lower index back by 5 pixels
-        if i < j {
-            match cars[i].current_direction.as_str() {
-                "West" => cars[i].car_rect.x += 0.001,
-                "East" => cars[i].car_rect.x -= 0.001,
-                "North" => cars[i].car_rect.y += 0.001,
-                "South" => cars[i].car_rect.y -= 0.001,
-                _ => {}
-            }
-        } else {
-            match cars[j].current_direction.as_str() {
-                "West" => cars[j].car_rect.x += 0.001,
-                "East" => cars[j].car_rect.x -= 0.001,
-                "North" => cars[j].car_rect.y += 0.001,
-                "South" => cars[j].car_rect.y -= 0.001,
-                _ => {}
-            }
-        }
+        // Move the car with the lower index back along its path
+        let index = i.min(j);
+        cars[index].distance -= 0.001;
+        cars[index].follow_path();
     }
 }
 
-impl Car {
-    /// Create a new Car with some randomized behavior, direction, spawn points, etc.
-    pub fn new(id: u32, randomized_behavior: &str, initial_direction: &str) -> Self {
-        let mut rng = rand::thread_rng();
-        let random_speed = rng.gen_range(0.8..2.0);
-
-        // Calculate lane center offset (half of LINE_SPACING minus half of car width/height)
-        let lane_center_h = (LINE_SPACING as f32 / 2.0) - (CAR_SIZE.y / 2.75);
-        let lane_center_v = (LINE_SPACING as f32 / 2.0) - (CAR_SIZE.x / 2.75);
-
-        // Update spawn points to match window size using LINE_SPACING
-        let spawning = match randomized_behavior {
-            // Right side spawns (x = window edge + 50, y = different lanes)
-            "RU" => Vec2::new(
-                (WINDOW_SIZE + 50) as f32,
-                (LINE_SPACING * 4) as f32 + lane_center_h,
-            ),
-            "RL" => Vec2::new(
-                (WINDOW_SIZE + 50) as f32,
-                (LINE_SPACING * 5) as f32 + lane_center_h,
-            ),
-            "RD" => Vec2::new(
-                (WINDOW_SIZE + 50) as f32,
-                (LINE_SPACING * 6) as f32 + lane_center_h,
-            ),
-
-            // Down side spawns (x = different lanes, y = window edge + 50)
-            "DU" => Vec2::new(
-                (LINE_SPACING * 8) as f32 + lane_center_v,
-                (WINDOW_SIZE + 50) as f32,
-            ),
-            "DL" => Vec2::new(
-                (LINE_SPACING * 7) as f32 + lane_center_v,
-                (WINDOW_SIZE + 50) as f32,
-            ),
-            "DR" => Vec2::new(
-                (LINE_SPACING * 9) as f32 + lane_center_v,
-                (WINDOW_SIZE + 50) as f32,
-            ),
-
-            // Left side spawns (x = -50, y = different lanes)
-            "LU" => Vec2::new(-50., (LINE_SPACING * 7) as f32 + lane_center_h),
-            "LR" => Vec2::new(-50., (LINE_SPACING * 8) as f32 + lane_center_h),
-            "LD" => Vec2::new(-50., (LINE_SPACING * 9) as f32 + lane_center_h),
-
-            // Up side spawns (x = different lanes, y = -50)
-            "UD" => Vec2::new((LINE_SPACING * 5) as f32 + lane_center_v, -50.),
-            "UR" => Vec2::new((LINE_SPACING * 6) as f32 + lane_center_v, -50.),
-            "UL" => Vec2::new((LINE_SPACING * 4) as f32 + lane_center_v, -50.),
-            _ => panic!("Unexpected lane"),
-        };
+/// Closed polyline around `rect`, for drawing with `draw_lines`.
+pub fn outline(rect: &OrientedRect) -> [SdlPoint; 5] {
+    let corners = rect.corners();
+    let point = |corner: Vec2| SdlPoint::new(corner.x as i32, corner.y as i32);
+    [
+        point(corners[0]),
+        point(corners[1]),
+        point(corners[2]),
+        point(corners[3]),
+        point(corners[0]),
+    ]
+}
 
-        // Determine the car rectangle dimensions depending on direction
-        let car_rect = if initial_direction == "West" || initial_direction == "East" {
-            FRect::new(spawning.x, spawning.y, CAR_SIZE.x, CAR_SIZE.y)
-        } else {
-            FRect::new(spawning.x, spawning.y, CAR_SIZE.y, CAR_SIZE.x)
-        };
+/// Car body of `size` centered on `pose`.
+fn body_at(pose: Pose, size: Vec2) -> OrientedRect {
+    OrientedRect::new(pose.position, size, pose.heading)
+}
 
-        // Radar rect (initially behind or ahead of the car, depending on direction)
-        let radar = FRect::new(
-            spawning.x - RADAR_SIZE.x,
-            spawning.y,
-            RADAR_SIZE.x,
-            RADAR_SIZE.y,
-        );
+/// Smallest gap to other cars a new car may spawn with, in pixels.
+pub const MIN_SPAWN_GAP: f32 = 3.0;
+/// Length of each probe the radar casts along the path, in pixels.
+const RADAR_STEP: f32 = 1.0;
+/// Length of the stretches of road the radar first checks for cars, in pixels.
+const RADAR_STRETCH: f32 = 16.0;
+
+/// Footprint of a `width` pixels wide lane from `from` to `to` pixels along
+/// `path`, approximated by a rectangle at the pose halfway in between.
+fn lane_ahead(path: &Path, from: f32, to: f32, width: f32) -> OrientedRect {
+    let middle = path.pose_at((from + to) / 2.0);
+    OrientedRect::new(middle.position, Vec2::new(to - from, width), middle.heading)
+}
 
-        // Destination point based on behavior - using LINE_SPACING for consistency
-        let dest_point = match randomized_behavior {
-            "RU" => Vec2::new((LINE_SPACING * 7) as f32, -50.),
-            "RL" => Vec2::new(-50., (LINE_SPACING * 5) as f32 + lane_center_h),
-            "RD" => Vec2::new((LINE_SPACING * 7) as f32, WINDOW_SIZE as f32),
-            "DU" => Vec2::new((LINE_SPACING * 8) as f32 + lane_center_v, -50.),
-            "DL" => Vec2::new(-50., (LINE_SPACING * 7) as f32 + lane_center_h),
-            "DR" => Vec2::new(
-                WINDOW_SIZE as f32,
-                (LINE_SPACING * 7) as f32 + lane_center_h,
-            ),
-            "LU" => Vec2::new((LINE_SPACING * 7) as f32, -50.),
-            "LR" => Vec2::new(
-                WINDOW_SIZE as f32,
-                (LINE_SPACING * 7) as f32 + lane_center_h,
-            ),
-            "LD" => Vec2::new((LINE_SPACING * 7) as f32, WINDOW_SIZE as f32),
-            "UD" => Vec2::new(
-                (LINE_SPACING * 5) as f32 + lane_center_v,
-                WINDOW_SIZE as f32,
-            ),
-            "UR" => Vec2::new(
-                WINDOW_SIZE as f32,
-                (LINE_SPACING * 5) as f32 + lane_center_h,
-            ),
-            "UL" => Vec2::new(-50., (LINE_SPACING * 5) as f32 + lane_center_h),
-            _ => panic!("Unexpected lane"),
+impl Car {
+    /// Create a new Car following `route`, spawned on `spawn_tick`, shaped
+    /// and driven as `scenario` says but with a cruising speed rolled from `rng`.
+    pub fn new(
+        id: u32,
+        route: Route,
+        spawn_tick: u64,
+        scenario: &Scenario,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let vehicles = &scenario.vehicles;
+        let driver = IdmParams {
+            desired_speed: rng.gen_range(vehicles.min_speed..=vehicles.max_speed),
+            ..scenario.driver
         };
+        let car_size = Dimensions::new(vehicles.length, vehicles.width);
+        let radar_size = Dimensions::new(vehicles.radar_length, vehicles.width);
+
+        // Spawn just outside the window, in the route's entry lane
+        let path = Path::for_route(route, &scenario.layout, vehicles.length);
+        let pose = path.pose_at(0.0);
+        let body = body_at(pose, car_size.size());
+
+        // Radar starts at full length, straight ahead
+        let front = car_size.long_edge / 2.0;
+        let radar = lane_ahead(
+            &path,
+            front,
+            front + radar_size.long_edge,
+            radar_size.short_edge,
+        );
 
         Car {
             id,
-            lifetime: Instant::now(),
-            spawn_point: spawning,
-            car_rect,
-            current_direction: initial_direction.to_string(),
-            current_speed: random_speed,
-            randomized_initial_speed: random_speed,
+            spawn_tick,
+            entry_tick: None,
+            exit_tick: None,
+            wait_ticks: 0,
+            waiting_since: None,
+            spawn_point: pose.position,
+            body,
+            car_rect: body.bounding_box(),
+            current_direction: pose.direction(),
+            current_speed: driver.desired_speed,
+            acceleration: 0.0,
+            jerk: 0.0,
+            comfort: Comfort::default(),
+            driver,
             radar,
-            proximity: RADAR_SIZE.x,
+            proximity: radar_size.long_edge,
+            leader_speed: None,
             has_turned: false,
-            behavior_code: randomized_behavior.to_string(),
-            waiting_flag: false,
-
-            car_size: Dimensions {
-                long_edge: 43.,
-                short_edge: 33.,
-                delta_edge: CAR_SIZE.x - CAR_SIZE.y,
-            },
-            radar_size: Dimensions {
-                long_edge: 43.,
-                short_edge: 33.,
-                delta_edge: CAR_SIZE.x - CAR_SIZE.y,
-            },
-            dest_point,
+            route,
+            path,
+            distance: 0.0,
+            heading: pose.heading,
+            car_size,
+            radar_size,
             intersection_state: IntersectionState::Before,
+            stop_line_gap: None,
+            right_of_way: false,
         }
     }
 
+    /// Update the body, heading and direction from `distance` along the path.
+    fn follow_path(&mut self) {
+        let pose = self.path.pose_at(self.distance);
+        self.body = body_at(pose, self.car_size.size());
+        self.car_rect = self.body.bounding_box();
+        self.heading = pose.heading;
+        self.current_direction = pose.direction();
+        self.has_turned = self.path.turn_end() > 0.0 && self.distance >= self.path.turn_end();
+    }
+
+    /// Whether the car has driven off the end of its path.
+    pub fn has_arrived(&self) -> bool {
+        self.distance >= self.path.length()
+    }
+
     /// Attempt to spawn a new Car if it doesn't intersect with any existing Car.
+    /// Returns whether the car was spawned.
     pub fn spawn_if_can(
         cars_ref: &mut Vec<Car>,
         next_id: u32,
-        randomized_behavior: &str,
-        initial_direction: &str,
-    ) {
-        let possible_new_car = Car::new(next_id, randomized_behavior, initial_direction);
-        // If none intersects and we don't exceed capacity
-        if !cars_ref.iter().any(|other_car| {
-            possible_new_car
-                .car_rect
-                .intersect(other_car.car_rect)
-                .is_some()
-        }) && cars_ref.len() < 9999
+        route: Route,
+        spawn_tick: u64,
+        scenario: &Scenario,
+        rng: &mut impl Rng,
+    ) -> bool {
+        let mut possible_new_car = Car::new(next_id, route, spawn_tick, scenario, rng);
+        // If it doesn't touch anyone and we don't exceed capacity
+        if cars_ref
+            .iter()
+            .all(|other_car| possible_new_car.body.distance(&other_car.body) > MIN_SPAWN_GAP)
+            && cars_ref.len() < scenario.vehicles.max_cars
         {
+            // Come in no faster than the driver can comfortably brake from
+            // for whatever is ahead. The new car isn't in `cars_ref` yet, so
+            // there's no index of its own to skip.
+            possible_new_car.update_radar(cars_ref.len(), cars_ref);
+            if let Some(leader_speed) = possible_new_car.leader_speed {
+                let leader = Some((possible_new_car.proximity, leader_speed));
+                let driver = possible_new_car.driver;
+                let speed = &mut possible_new_car.current_speed;
+                while *speed > 0.0
+                    && driver.acceleration(*speed, leader) < -driver.comfortable_deceleration
+                {
+                    *speed = (*speed - 1.0).max(0.0);
+                }
+            }
             cars_ref.push(possible_new_car);
-            increment_spawn_count();
+            true
+        } else {
+            false
         }
     }
 
-    /// If approaching an intersection, check if this Car needs to wait
-    /// based on other cars' positions/behaviors.
-    pub fn communicate_with_intersection(
-        &mut self,
-        cars_ref: &Vec<Car>,
-        core_intersection: &FRect,
-    ) {
-        let mut temp_cars = cars_ref.clone();
-        temp_cars.retain(|car| car.id != self.id);
-    
-        let is_in_radar = self.radar.intersect(*core_intersection).is_some();
-        let is_outside_intersection = self.car_rect.intersect(*core_intersection).is_none();
-    
-        if is_in_radar && is_outside_intersection {
-            self.waiting_flag = false;
-    
-            // Count cars currently turning left in the intersection
-            let left_turning_cars = temp_cars
-                .iter()
-                .filter(|car| {
-                    car.car_rect.intersect(*core_intersection).is_some()
-                        && matches!(car.behavior_code.as_str(), "RD" | "LU" | "UR" | "DL")
-                        && !car.waiting_flag  // Only count cars that are actually moving
-                })
-                .count();
-    
-            // For all left-turning behaviors
-            if matches!(self.behavior_code.as_str(), "RD" | "LU" | "UR" | "DL") {
-                // If there are already 3 or more cars turning left, make this car wait
-                if left_turning_cars >= 3 {
-                    self.waiting_flag = true;
-                    return;
-                }
-            }
-    
-            // Original behavior for other cases
-            match self.behavior_code.as_str() {
-                "LR" | "UR" | "DL" => {
-                    if left_turning_cars >= 2
-                        || temp_cars.iter().any(|car| {
-                            matches!(car.behavior_code.as_str(), "LR" | "UR" | "RL" | "DL")
-                                && car.car_rect.intersect(*core_intersection).is_some()
-                        })
-                    {
-                        self.waiting_flag = true;
-                    }
-                }
-                "LU" | "RD" | "RL" | "UD" | "DU" => {
-                    if temp_cars.iter().any(|car| {
-                        car.behavior_code == self.behavior_code
-                            && car.car_rect.intersect(*core_intersection).is_some()
-                    }) {
-                        self.waiting_flag = true;
-                    }
-                }
-                _ => {}
-            }
+    /// Distance from the front of the car to the edge of the core intersection,
+    /// measured along the direction the car entered in.
+    pub fn distance_to_stop_line(&self, core_intersection: &FRect) -> f32 {
+        match self.route.initial_direction() {
+            Direction::West => self.car_rect.x - (core_intersection.x + core_intersection.w),
+            Direction::East => core_intersection.x - (self.car_rect.x + self.car_rect.w),
+            Direction::North => self.car_rect.y - (core_intersection.y + core_intersection.h),
+            Direction::South => core_intersection.y - (self.car_rect.y + self.car_rect.h),
         }
     }
-    
-    /// Move one step in the current direction if it doesn't cause a collision.
-    pub fn move_one_step_if_no_collide(&mut self, temp_cars: &mut Vec<Car>, intersection: &FRect) {
-        // Copy so we can test a hypothetical move
-        let mut temp_self_car = self.clone();
-        temp_cars.retain(|car| temp_self_car.id != car.id);
-
-        if self.waiting_flag {
-            // println!("Car {} is waiting and not moving", self.id);
-            return;
-        }
 
-        match self.current_direction.as_str() {
-            "West" => {
-                temp_self_car.car_rect.x -= temp_self_car.current_speed;
-                if temp_cars
-                    .iter()
-                    .all(|car| temp_self_car.car_rect.intersect(car.car_rect).is_none())
-                {
-                    self.car_rect.x -= self.current_speed;
-                }
-            }
-            "North" => {
-                temp_self_car.car_rect.y -= temp_self_car.current_speed;
-                if temp_cars
-                    .iter()
-                    .all(|car| temp_self_car.car_rect.intersect(car.car_rect).is_none())
-                {
-                    self.car_rect.y -= self.current_speed;
-                }
-            }
-            "South" => {
-                temp_self_car.car_rect.y += temp_self_car.current_speed;
-                if temp_cars
-                    .iter()
-                    .all(|car| temp_self_car.car_rect.intersect(car.car_rect).is_none())
-                {
-                    self.car_rect.y += self.current_speed;
-                }
-            }
-            "East" => {
-                temp_self_car.car_rect.x += self.current_speed;
-                if temp_cars
-                    .iter()
-                    .all(|car| temp_self_car.car_rect.intersect(car.car_rect).is_none())
-                {
-                    self.car_rect.x += self.current_speed;
-                }
-            }
-            _ => {}
+    /// Whether the car has not entered the core intersection yet
+    /// but is within `lookahead` pixels of its stop line.
+    pub fn is_approaching(&self, core_intersection: &FRect, lookahead: f32) -> bool {
+        self.intersection_state == IntersectionState::Before
+            && self.distance_to_stop_line(core_intersection) <= lookahead
+    }
+
+    /// Move as if the road were empty: speed up towards the desired speed
+    /// along the path. Used to predict a car's path.
+    pub fn advance_unobstructed(&mut self, core_intersection: &FRect, dt: f32) {
+        self.proximity = self.radar_size.long_edge;
+        self.leader_speed = None;
+        self.adjust_current_speed(dt);
+        self.move_one_step_if_no_collide(&mut Vec::new(), core_intersection, dt);
+    }
+
+    /// Move `dt` seconds worth of distance along the path
+    /// if it doesn't cause a collision.
+    pub fn move_one_step_if_no_collide(
+        &mut self,
+        temp_cars: &mut Vec<Car>,
+        intersection: &FRect,
+        dt: f32,
+    ) {
+        temp_cars.retain(|car| self.id != car.id);
+
+        // Test the hypothetical move before committing to it
+        let step = self.current_speed * dt;
+        let next_body = body_at(
+            self.path.pose_at(self.distance + step),
+            self.car_size.size(),
+        );
+        if temp_cars.iter().all(|car| !next_body.intersects(&car.body)) {
+            self.distance += step;
+            self.follow_path();
         }
 
         // Update intersection state if the car is inside the intersection
-        if self.car_rect.intersect(*intersection).is_some() {
+        if self.body.intersects(&OrientedRect::from(*intersection)) {
             self.intersection_state = IntersectionState::Inside;
         }
     }
@@ -393,344 +355,106 @@ impl Car {
     //         .any(|temp_car| temp_car.car_rect.intersect(self.car_rect).is_some())
     // }
 
-    /// Update the 'radar' rectangle based on our current direction and nearby cars.
-    pub fn update_radar(&mut self, car_index: usize, temp_cars: &Vec<Car>) {
-        match self.current_direction.as_str() {
-            "West" => {
-                self.radar.x = self.car_rect.x - self.radar_size.long_edge;
-                self.radar.y = self.car_rect.y;
-                self.radar.w = self.radar_size.long_edge;
-                self.radar.h = self.radar_size.short_edge;
-
-                for (other_index, other_car) in temp_cars.iter().enumerate() {
-                    if car_index != other_index
-                        && self.radar.intersect(other_car.car_rect).is_some()
-                    {
-                        self.radar.x = other_car.car_rect.x + other_car.car_rect.w;
-                    }
-                    self.radar.w = (self.car_rect.x - self.radar.x).abs().min(43.);
-                }
-            }
-            "North" => {
-                self.radar.x = self.car_rect.x;
-                self.radar.y = self.car_rect.y - self.radar_size.long_edge;
-                for (other_index, other_car) in temp_cars.iter().enumerate() {
-                    if car_index != other_index
-                        && self.radar.intersect(other_car.car_rect).is_some()
-                    {
-                        self.radar.y = other_car.car_rect.y + other_car.car_rect.h;
-                    }
-                    self.radar.h = (self.car_rect.y - self.radar.y).abs().min(43.);
-                    self.radar.w = 35.;
-                }
-            }
-            "South" => {
-                self.radar.x = self.car_rect.x;
-                self.radar.y = self.car_rect.y + self.radar_size.long_edge;
-                self.radar.w = self.radar_size.short_edge;
-                self.radar.h = self.radar_size.long_edge;
-                for (other_index, other_car) in temp_cars.iter().enumerate() {
-                    if car_index != other_index
-                        && self.radar.intersect(other_car.car_rect).is_some()
-                    {
-                        self.radar.h =
-                            other_car.car_rect.y - (self.car_rect.y + self.car_size.long_edge);
-                    }
-                }
-            }
-            "East" => {
-                self.radar.x = self.car_rect.x + self.car_rect.w;
-                self.radar.y = self.car_rect.y;
-                self.radar.w = self.radar_size.long_edge;
-                self.radar.h = self.radar_size.short_edge;
-
-                for (other_index, other_car) in temp_cars.iter().enumerate() {
-                    if car_index != other_index
-                        && self.radar.intersect(other_car.car_rect).is_some()
-                    {
-                        self.radar.w = other_car.car_rect.x - (self.car_rect.x + self.car_rect.w);
-                    }
-                    // Additional logic if you have radar-radar checks, etc.
-                }
-            }
-            _ => {}
-        }
-    }
-
-    /// Adjust the Car's current speed based on radar distance.
-    pub fn adjust_current_speed(&mut self) {
-        if self.current_direction == "West" || self.current_direction == "East" {
-            match self.radar.w {
-                w if w <= 3.0 => {
-                    self.current_speed = 0.0;
-                }
-                w if w <= 30.0 => {
-                    self.current_speed = self.randomized_initial_speed * 0.25;
-                }
-                w if w <= 39.0 => {
-                    self.current_speed = self.randomized_initial_speed * 0.50;
-                }
-                _ => self.current_speed = self.randomized_initial_speed,
-            }
-        } else if self.current_direction == "North" || self.current_direction == "South" {
-            match self.radar.h {
-                h if h <= 3.0 => {
-                    self.current_speed = 0.0;
-                }
-                h if h <= 20.0 => {
-                    self.current_speed = self.randomized_initial_speed * 0.25;
-                }
-                h if h <= 39.0 => {
-                    self.current_speed = self.randomized_initial_speed * 0.50;
-                }
-                _ => self.current_speed = self.randomized_initial_speed,
-            }
-        }
-
-        // Clamp final speed to ensure it never exceeds the initial base
-        self.current_speed = self.current_speed.clamp(0.0, self.randomized_initial_speed);
-    }
-
-    /// Turn the Car if the conditions for turning (based on behavior_code) are met.
-    pub fn turn_if_can(&mut self, temp_cars: &Vec<Car>) {
-        // Add complete turning logic for all cases
-        match self.behavior_code.as_str() {
-            "RU" => self.turn_right_up(temp_cars),
-            "RD" => self.turn_right_down(temp_cars),
-            "LU" => self.turn_left_up(temp_cars),
-            "LD" => self.turn_left_down(temp_cars),
-            "UR" => self.turn_up_right(temp_cars),
-            "UL" => self.turn_up_left(temp_cars),
-            "DR" => self.turn_down_right(temp_cars),
-            "DL" => self.turn_down_left(temp_cars),
-            _ => {} // Straight paths don't turn
-        }
-    }
-
-    /// From: Right lane moving West
-    /// To: Upper lane moving North
-    fn turn_right_up(&mut self, temp_cars: &Vec<Car>) {
-        // Change turning point to use LINE_SPACING * 9
-        if !self.has_turned && self.car_rect.x <= (LINE_SPACING * 9) as f32 {
-            self.waiting_flag = true;
-            let mut clear_to_turn = true;
-
-            let temp_rect = FRect::new(
-                (LINE_SPACING * 9) as f32,
-                self.car_rect.y - (self.car_rect.w - self.car_rect.h).abs(),
-                self.car_rect.h,
-                self.car_rect.w,
-            );
-
-            for other_car in temp_cars {
-                if self.id != other_car.id && temp_rect.intersect(other_car.car_rect).is_some() {
-                    clear_to_turn = false;
-                }
-            }
-            if clear_to_turn {
-                self.car_rect = temp_rect;
-                self.waiting_flag = false;
-                self.current_direction = "North".to_string();
-                self.has_turned = true;
-            }
-        }
-    }
-
-    /// From: Right lane moving West
-    /// To: Lower lane moving South
-    fn turn_right_down(&mut self, temp_cars: &Vec<Car>) {
-        // Use same LINE_SPACING * 9 for consistency
-        if !self.has_turned && self.car_rect.x <= (LINE_SPACING * 6) as f32 + OFFSET {
-            self.waiting_flag = true;
-            let mut clear_to_turn = true;
-
-            let temp_rect = FRect::new(
-                (LINE_SPACING * 6) as f32 + OFFSET,
-                self.car_rect.y,
-                self.car_rect.h,
-                self.car_rect.w,
-            );
-
-            for other_car in temp_cars {
-                if self.id != other_car.id && temp_rect.intersect(other_car.car_rect).is_some() {
-                    clear_to_turn = false;
-                }
-            }
-            if clear_to_turn {
-                self.car_rect = temp_rect;
-                self.waiting_flag = false;
-                self.current_direction = "South".to_string();
-                self.has_turned = true;
-            }
-        }
-    }
-
-    /// From: Left lane moving East
-    /// To: Upper lane moving North
-    fn turn_left_up(&mut self, temp_cars: &Vec<Car>) {
-        if !self.has_turned && self.car_rect.x >= (LINE_SPACING * 7) as f32 + OFFSET {
-            self.waiting_flag = true;
-            let mut clear_to_turn = true;
-
-            let temp_rect = FRect::new(
-                (LINE_SPACING * 7) as f32 + OFFSET,
-                self.car_rect.y - (self.car_rect.w - self.car_rect.h).abs(),
-                self.car_rect.h,
-                self.car_rect.w,
-            );
-
-            for other_car in temp_cars {
-                if self.id != other_car.id && temp_rect.intersect(other_car.car_rect).is_some() {
-                    clear_to_turn = false;
-                }
-            }
-            if clear_to_turn {
-                self.car_rect = temp_rect;
-                self.waiting_flag = false;
-                self.current_direction = "North".to_string();
-                self.has_turned = true;
-            }
-        }
-    }
-
-    /// From: Left lane moving East
-    /// To: Lower lane moving South
-    fn turn_left_down(&mut self, temp_cars: &Vec<Car>) {
-        if !self.has_turned && self.car_rect.x >= (LINE_SPACING * 4) as f32 + OFFSET {
-            self.waiting_flag = true;
-            let mut clear_to_turn = true;
-
-            let temp_rect = FRect::new(
-                (LINE_SPACING * 4) as f32 + OFFSET,
-                self.car_rect.y,
-                self.car_rect.h,
-                self.car_rect.w,
-            );
-
-            for other_car in temp_cars {
-                if self.id != other_car.id && temp_rect.intersect(other_car.car_rect).is_some() {
-                    clear_to_turn = false;
-                }
-            }
-            if clear_to_turn {
-                self.car_rect = temp_rect;
-                self.waiting_flag = false;
-                self.current_direction = "South".to_string();
-                self.has_turned = true;
+    /// Cast the radar forward along the path, up to one radar length past
+    /// the front bumper, and stop it at the nearest car in the way or at the
+    /// stop line if the policy is holding us. The free distance it found
+    /// ends up in `proximity`, and the speed of what it found in `leader_speed`.
+    pub fn update_radar(&mut self, car_index: usize, temp_cars: &[Car]) {
+        let front = self.distance + self.car_size.long_edge / 2.0;
+        let range = self.radar_size.long_edge;
+        let width = self.radar_size.short_edge;
+
+        // Only cars within reach of the radar are worth probing, and the
+        // walk can skip ahead to the first stretch of road they reach into.
+        // The stretches follow the path, so cars ahead where it bends into
+        // a turn are still found. They're a little wider than the probes to
+        // make up for cutting corners on the bend.
+        let stretches: Vec<OrientedRect> = (0..(range / RADAR_STRETCH).ceil() as usize)
+            .map(|i| {
+                let from = front + i as f32 * RADAR_STRETCH;
+                lane_ahead(
+                    &self.path,
+                    from,
+                    from + RADAR_STRETCH,
+                    width + RADAR_STRETCH / 2.0,
+                )
+            })
+            .collect();
+        let mut first_stretch = stretches.len();
+        let nearby: Vec<&Car> = temp_cars
+            .iter()
+            .enumerate()
+            .filter(|&(other_index, other_car)| {
+                other_index != car_index
+                    && (!self.right_of_way || other_car.route == self.route)
+                    && other_car.body.center.distance(self.body.center)
+                        <= 2.0 * self.car_size.long_edge + range
+            })
+            .filter_map(|(_, other_car)| {
+                let reached = stretches
+                    .iter()
+                    .position(|stretch| stretch.intersects(&other_car.body))?;
+                first_stretch = first_stretch.min(reached);
+                Some(other_car)
+            })
+            .collect();
+
+        // Walk forward one probe at a time until something is hit
+        let mut gap = (first_stretch as f32 * RADAR_STRETCH).min(range);
+        let mut leader_speed = None;
+        while gap < range {
+            let probe = lane_ahead(&self.path, front + gap, front + gap + RADAR_STEP, width);
+            if let Some(leader) = nearby
+                .iter()
+                .find(|other_car| probe.intersects(&other_car.body))
+            {
+                // Only the part of its velocity along our heading matters,
+                // a car crossing our path counts as standing still
+                leader_speed = Some(leader.current_speed * (leader.heading - self.heading).cos());
+                break;
             }
+            gap += RADAR_STEP;
         }
-    }
+        let mut gap = gap.min(range);
 
-    /// From: Upper lane moving South
-    /// To: Right lane moving East
-    fn turn_up_right(&mut self, temp_cars: &Vec<Car>) {
-        if !self.has_turned && self.car_rect.y >= (LINE_SPACING * 7) as f32 + OFFSET {
-            self.waiting_flag = true;
-            let mut clear_to_turn = true;
-
-            let temp_rect = FRect::new(
-                self.car_rect.x,
-                (LINE_SPACING * 7) as f32 + OFFSET,
-                self.car_rect.h,
-                self.car_rect.w,
-            );
-
-            for other_car in temp_cars {
-                if self.id != other_car.id && temp_rect.intersect(other_car.car_rect).is_some() {
-                    clear_to_turn = false;
-                }
-            }
-            if clear_to_turn {
-                self.car_rect = temp_rect;
-                self.waiting_flag = false;
-                self.current_direction = "East".to_string();
-                self.has_turned = true;
+        // The stop line is a leader that doesn't move
+        if let Some(stop_line) = self.stop_line_gap {
+            if stop_line < gap {
+                gap = stop_line.max(0.0);
+                leader_speed = Some(0.0);
             }
         }
-    }
 
-    /// From: Upper lane moving South
-    /// To: Left lane moving West
-    fn turn_up_left(&mut self, temp_cars: &Vec<Car>) {
-        if !self.has_turned && self.car_rect.y >= (LINE_SPACING * 4) as f32 + OFFSET {
-            self.waiting_flag = true;
-            let mut clear_to_turn = true;
-
-            let temp_rect = FRect::new(
-                self.car_rect.x - (self.car_rect.w - self.car_rect.h).abs(),
-                (LINE_SPACING * 4) as f32 + OFFSET,
-                self.car_rect.h,
-                self.car_rect.w,
-            );
-
-            for other_car in temp_cars {
-                if self.id != other_car.id && temp_rect.intersect(other_car.car_rect).is_some() {
-                    clear_to_turn = false;
-                }
-            }
-            if clear_to_turn {
-                self.car_rect = temp_rect;
-                self.waiting_flag = false;
-                self.current_direction = "West".to_string();
-                self.has_turned = true;
-            }
-        }
+        self.proximity = gap;
+        self.leader_speed = leader_speed;
+        self.radar = lane_ahead(&self.path, front, front + self.proximity, width);
     }
 
-    /// From: Lower lane moving North
-    /// To: Right lane moving East
-    fn turn_down_right(&mut self, temp_cars: &Vec<Car>) {
-        if !self.has_turned && self.car_rect.y <= (LINE_SPACING * 9) as f32 + OFFSET {
-            self.waiting_flag = true;
-            let mut clear_to_turn = true;
-
-            let temp_rect = FRect::new(
-                self.car_rect.x,
-                (LINE_SPACING * 9) as f32 + OFFSET,
-                self.car_rect.h,
-                self.car_rect.w,
-            );
-
-            for other_car in temp_cars {
-                if self.id != other_car.id && temp_rect.intersect(other_car.car_rect).is_some() {
-                    clear_to_turn = false;
-                }
-            }
-            if clear_to_turn {
-                self.car_rect = temp_rect;
-                self.waiting_flag = false;
-                self.current_direction = "East".to_string();
-                self.has_turned = true;
-            }
-        }
-    }
-
-    /// From: Lower lane moving North
-    /// To: Left lane moving West
-    fn turn_down_left(&mut self, temp_cars: &Vec<Car>) {
-        if !self.has_turned && self.car_rect.y <= (LINE_SPACING * 6) as f32 + OFFSET {
-            self.waiting_flag = true;
-            let mut clear_to_turn = true;
-
-            let temp_rect = FRect::new(
-                self.car_rect.x - (self.car_rect.w - self.car_rect.h).abs(),
-                (LINE_SPACING * 6) as f32 + OFFSET,
-                self.car_rect.h,
-                self.car_rect.w,
-            );
-
-            for other_car in temp_cars {
-                if self.id != other_car.id && temp_rect.intersect(other_car.car_rect).is_some() {
-                    clear_to_turn = false;
-                }
-            }
-            if clear_to_turn {
-                self.car_rect = temp_rect;
-                self.waiting_flag = false;
-                self.current_direction = "West".to_string();
-                self.has_turned = true;
-            }
-        }
+    /// Accelerate or brake for `dt` seconds following the Intelligent
+    /// Driver Model, using the gap and leader speed from the radar.
+    pub fn adjust_current_speed(&mut self, dt: f32) {
+        let previous_speed = self.current_speed;
+        let previous_acceleration = self.acceleration;
+
+        let leader = self.leader_speed.map(|speed| (self.proximity, speed));
+        // The model may ask for more braking than the car can give
+        let acceleration = self
+            .driver
+            .acceleration(self.current_speed, leader)
+            .max(-self.driver.max_deceleration);
+        self.current_speed += acceleration * dt;
+
+        // Clamp final speed so the car neither reverses nor speeds
+        self.current_speed = self.current_speed.clamp(0.0, self.driver.desired_speed);
+
+        // What the passengers feel is the speed change that actually happened
+        self.acceleration = (self.current_speed - previous_speed) / dt;
+        self.jerk = (self.acceleration - previous_acceleration) / dt;
+        self.comfort.record(
+            self.acceleration,
+            self.jerk,
+            self.driver.comfortable_deceleration,
+        );
     }
 
     /// Draw the car, radar, and/or debugging overlay using SDL2.
@@ -744,69 +468,107 @@ impl Car {
         car_texture: &Texture,
         debug: bool,
     ) -> Result<(), String> {
-        // Make the car visible by default
-        canvas.set_draw_color(sdl2::pixels::Color::RGB(255, 255, 255));
-        let car_rect = SdlRect::new(
-            self.car_rect.x as i32,
-            self.car_rect.y as i32,
-            self.car_rect.w as u32,
-            self.car_rect.h as u32,
-        );
-
-        // If debug, draw the radar rect and car rect with partial alpha
+        // If debug, outline the radar and car body with partial alpha
         if debug {
-            canvas.fill_rect(car_rect)?;
             // Enable blending so alpha is visible
             canvas.set_blend_mode(BlendMode::Blend);
 
-            // Draw Radar with low alpha (like macroquad's 0.1)
-            canvas.set_draw_color(sdl2::pixels::Color::RGBA(255, 0, 0, 25));
-            let radar_rect = SdlRect::new(
-                self.radar.x as i32,
-                self.radar.y as i32,
-                self.radar.w as u32,
-                self.radar.h as u32,
-            );
-            canvas.fill_rect(radar_rect)?;
-
-            // Draw Car rect with alpha (like macroquad's 0.3)
-            canvas.set_draw_color(sdl2::pixels::Color::RGBA(0, 255, 0, 77));
-            let car_rect = SdlRect::new(
-                self.car_rect.x as i32,
-                self.car_rect.y as i32,
-                self.car_rect.w as u32,
-                self.car_rect.h as u32,
-            );
-            canvas.fill_rect(car_rect)?;
+            // Outline the radar (rotated, so it can't be filled as an SDL rect)
+            canvas.set_draw_color(sdl2::pixels::Color::RGBA(255, 0, 0, 128));
+            canvas.draw_lines(outline(&self.radar).as_slice())?;
+
+            // Outline the car body the same way
+            canvas.set_draw_color(sdl2::pixels::Color::RGBA(0, 255, 0, 200));
+            canvas.draw_lines(outline(&self.body).as_slice())?;
         }
 
-        // Draw the car image with rotation based on direction
-        let (angle, offset_x, offset_y) = match self.current_direction.as_str() {
-            "West" => (0.0, 1.5, 1.5),
-            "North" => (90.0, -3.0, 7.0),
-            "South" => (270.0, -3.0, 7.0),
-            "East" => (180.0, 2.0, 2.0),
-            _ => (0.0, 0.0, 0.0),
-        };
+        draw_sprite(
+            canvas,
+            car_texture,
+            self.body.center,
+            self.car_size.size(),
+            self.heading,
+        )
+    }
+}
 
-        // We'll draw the car at a fixed 40x30 area (like your macroquad code),
-        // offset by (offset_x, offset_y) to match your original.
-        let dest_rect = SdlRect::new(
-            (self.car_rect.x + offset_x) as i32,
-            (self.car_rect.y + offset_y) as i32,
-            40,
-            30,
-        );
+/// Draw the car image stretched to `size` (length, width), centered on
+/// `center` and rotated to `heading`.
+pub fn draw_sprite(
+    canvas: &mut WindowCanvas,
+    car_texture: &Texture,
+    center: Vec2,
+    size: Vec2,
+    heading: f32,
+) -> Result<(), String> {
+    // The sprite faces West, so a heading of PI (West) means no rotation.
+    let angle = heading.to_degrees() as f64 - 180.0;
+
+    // Unrotated, the car's length runs across the screen, centered on the
+    // middle of the car.
+    let dest_rect = SdlRect::new(
+        (center.x - size.x / 2.0) as i32,
+        (center.y - size.y / 2.0) as i32,
+        size.x as u32,
+        size.y as u32,
+    );
+
+    // Render with rotation. `copy_ex` uses degrees, so we pass `angle` directly.
+    //
+    // - `src` is None => use the entire texture
+    // - `dest_rect` is the output rectangle
+    // - `angle` in degrees
+    // - `center` is None => rotation around the middle of `dest_rect`
+    // - `flip_horizontal/flip_vertical` are false
+    canvas.copy_ex(car_texture, None, dest_rect, angle, None, false, false)
+}
 
-        // Render with rotation. `copy_ex` uses degrees, so we pass `angle` directly.
-        //
-        // - `src` is None => use the entire texture
-        // - `dest_rect` is the output rectangle
-        // - `angle` in degrees
-        // - `center` is None => rotation around top-left
-        // - `flip_horizontal/flip_vertical` are false
-        canvas.copy_ex(car_texture, None, dest_rect, angle, None, false, false)?;
+/// A car on `route` with its center `distance` along the path, at `speed`.
+#[cfg(test)]
+pub fn car_at(id: u32, route: Route, scenario: &Scenario, distance: f32, speed: f32) -> Car {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    let mut car = Car::new(id, route, 0, scenario, &mut StdRng::seed_from_u64(0));
+    car.distance = distance;
+    car.current_speed = speed;
+    car.follow_path();
+    car
+}
 
-        Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::route::{Approach, Turn};
+
+    #[test]
+    fn radar_sees_a_stopped_car_where_a_right_turn_begins() {
+        let mut scenario = Scenario::default();
+        scenario.layout.window_size = 600;
+        let route = Route::new(Approach::Right, Turn::Right);
+        let length = scenario.vehicles.length;
+
+        // The arc starts where the heading first changes
+        let path = Path::for_route(route, &scenario.layout, length);
+        let heading = path.pose_at(0.0).heading;
+        let arc_start = (0..)
+            .map(|distance| distance as f32)
+            .find(|&distance| path.pose_at(distance).heading != heading)
+            .unwrap();
+
+        // Waiting at the stop line, with the follower queued right behind,
+        // so the middle of its radar is already past the turn
+        let leader = car_at(0, route, &scenario, arc_start - length / 2.0, 0.0);
+        let gap = scenario.driver.min_gap;
+        let mut follower = car_at(1, route, &scenario, leader.distance - length - gap, 0.0);
+        let cars = vec![leader, follower.clone()];
+        follower.update_radar(1, &cars);
+
+        assert!(
+            (follower.proximity - gap).abs() <= 2.0 * RADAR_STEP,
+            "proximity {}",
+            follower.proximity
+        );
+        assert_eq!(follower.leader_speed, Some(0.0));
     }
 }
@@ -0,0 +1,222 @@
+use crate::export::ExportFormat;
+use crate::idm::IdmParams;
+use crate::policy::PolicyKind;
+use crate::safety::SafetyThresholds;
+use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+
+/// Smart road intersection simulation.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Run the simulation in a window, driven from the keyboard.
+    Run(RunArgs),
+    /// Run the simulation without a window, with random spawning on,
+    /// for a fixed number of ticks.
+    Headless(HeadlessArgs),
+    /// Run headless over a range of seeds for every scenario and policy
+    /// given, in parallel, and compare the results.
+    Batch(BatchArgs),
+    /// Play back a file written with `run --record` in a window.
+    Replay(ReplayArgs),
+    /// Re-run a file written with `run --record` without a window and
+    /// report its metrics.
+    Report(ReportArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct RunArgs {
+    /// Seed for every random decision in the run (spawns, routes, speeds).
+    /// A random seed is picked and printed when omitted.
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    #[command(flatten)]
+    pub scenario: ScenarioArgs,
+
+    #[command(flatten)]
+    pub output: OutputArgs,
+
+    /// Pause the simulation when two cars collide and highlight them.
+    /// Press Space to resume.
+    #[arg(long)]
+    pub pause_on_collision: bool,
+
+    /// Record the seed, scenario and every input of the run to this file,
+    /// to be played back with `replay`.
+    #[arg(long, value_name = "FILE")]
+    pub record: Option<PathBuf>,
+
+    /// Also record where every car is on each tick, so the run can be
+    /// replayed even after the simulation code has changed.
+    #[arg(long, requires = "record")]
+    pub record_snapshots: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct HeadlessArgs {
+    /// Seed for every random decision in the run (spawns, routes, speeds).
+    /// A random seed is picked and printed when omitted.
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Number of ticks to simulate, at 60 ticks per second.
+    #[arg(long)]
+    pub ticks: u64,
+
+    #[command(flatten)]
+    pub scenario: ScenarioArgs,
+
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct BatchArgs {
+    /// Scenario files to run, each with every policy and seed. The built-in
+    /// scenario is run when none are given.
+    #[arg(long = "scenario", value_name = "FILE")]
+    pub scenarios: Vec<PathBuf>,
+
+    /// Policies to compare in every scenario, like `--policy heuristic,signal`.
+    /// Each scenario's own policy is used when none are given.
+    #[arg(long = "policy", value_enum, value_delimiter = ',')]
+    pub policies: Vec<PolicyKind>,
+
+    /// First seed to run.
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+
+    /// Number of seeds to run per scenario and policy, counting up from `--seed`.
+    #[arg(long, default_value_t = 10)]
+    pub runs: u64,
+
+    /// Number of ticks to simulate per run, at 60 ticks per second.
+    #[arg(long, default_value_t = 3600)]
+    pub ticks: u64,
+
+    /// Use this window size in every scenario instead of its own, in pixels.
+    #[arg(long)]
+    pub window_size: Option<u32>,
+
+    /// Number of runs to simulate at once. Defaults to one per CPU core.
+    #[arg(long)]
+    pub threads: Option<usize>,
+
+    /// Write the comparison table into this directory, as a `comparison`
+    /// file and as `comparison.md`, along with every run's summary, trip
+    /// records and time series in `<scenario>/<policy>/seed-<seed>` and a
+    /// summary of all runs per scenario. Scenarios are named after their
+    /// file, with `-2`, `-3`... added when several files share a name.
+    #[arg(long, value_name = "DIR")]
+    pub output: Option<PathBuf>,
+
+    /// File format of `--output`.
+    #[arg(long, value_enum, default_value_t = ExportFormat::Csv)]
+    pub format: ExportFormat,
+}
+
+#[derive(Args, Debug)]
+pub struct ReplayArgs {
+    /// File written with `run --record`.
+    pub file: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct ReportArgs {
+    /// File written with `run --record`.
+    pub file: PathBuf,
+
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+/// Where to export a single run.
+#[derive(Args, Debug)]
+pub struct OutputArgs {
+    /// Write the summary, trip records and per-tick time series of the run
+    /// into this directory when it ends.
+    #[arg(long, value_name = "DIR")]
+    pub output: Option<PathBuf>,
+
+    /// File format of `--output`.
+    #[arg(long, value_enum, default_value_t = ExportFormat::Csv)]
+    pub format: ExportFormat,
+}
+
+/// The scenario of a single run: a scenario file or the built-in one,
+/// tuned by the flags.
+#[derive(Args, Debug)]
+pub struct ScenarioArgs {
+    /// Load the layout, vehicles, demand, policy and driver settings from a
+    /// TOML or JSON scenario file instead of the tuning flags below.
+    #[arg(long, value_name = "FILE")]
+    pub scenario: Option<PathBuf>,
+
+    /// Intersection-management policy deciding which cars may enter the box.
+    /// Overrides the scenario's [default: heuristic].
+    #[arg(long, value_enum)]
+    pub policy: Option<PolicyKind>,
+
+    /// Width and height of the window, in pixels. Lanes are 1/14 of it wide,
+    /// and must leave cars room to turn right: 546 at least with the default
+    /// cars. Overrides the scenario's [default: 800].
+    #[arg(long)]
+    pub window_size: Option<u32>,
+
+    /// Green time of the through phases with `--policy signal`, in seconds.
+    #[arg(long, default_value_t = 12.0, conflicts_with = "scenario")]
+    pub signal_green: f32,
+
+    /// Green time of the protected left-turn phases, in seconds.
+    #[arg(long, default_value_t = 6.0, conflicts_with = "scenario")]
+    pub signal_left_green: f32,
+
+    /// Yellow time at the end of every phase, in seconds.
+    #[arg(long, default_value_t = 3.0, conflicts_with = "scenario")]
+    pub signal_yellow: f32,
+
+    /// All-red clearance time between phases, in seconds.
+    #[arg(long, default_value_t = 3.0, conflicts_with = "scenario")]
+    pub signal_all_red: f32,
+
+    /// Time gap drivers keep to the car in front, in seconds.
+    #[arg(long, default_value_t = IdmParams::default().time_headway, conflicts_with = "scenario")]
+    pub time_headway: f32,
+
+    /// Gap drivers keep to the car in front when standing still, in pixels.
+    #[arg(long, default_value_t = IdmParams::default().min_gap, conflicts_with = "scenario")]
+    pub min_gap: f32,
+
+    /// How hard drivers accelerate from a standstill, in px/s².
+    #[arg(long, default_value_t = IdmParams::default().max_acceleration, conflicts_with = "scenario")]
+    pub max_acceleration: f32,
+
+    /// How hard drivers are comfortable braking, in px/s².
+    #[arg(long, default_value_t = IdmParams::default().comfortable_deceleration, conflicts_with = "scenario")]
+    pub comfortable_deceleration: f32,
+
+    /// Hardest drivers can brake in an emergency, in px/s².
+    #[arg(long, default_value_t = IdmParams::default().max_deceleration, conflicts_with = "scenario")]
+    pub max_deceleration: f32,
+
+    /// Flag a close call when two cars would touch within this many seconds
+    /// at their current speeds.
+    #[arg(long, default_value_t = SafetyThresholds::default().time_to_collision, conflicts_with = "scenario")]
+    pub ttc_threshold: f32,
+
+    /// Flag a close call when a car enters a patch of the intersection this
+    /// soon after a crossing car left it, in seconds.
+    #[arg(long, default_value_t = SafetyThresholds::default().post_encroachment_time, conflicts_with = "scenario")]
+    pub pet_threshold: f32,
+
+    /// Projected gap between cars that counts as a near miss, in pixels.
+    #[arg(long, default_value_t = SafetyThresholds::default().safety_distance, conflicts_with = "scenario")]
+    pub safety_distance: f32,
+}
@@ -0,0 +1,56 @@
+use crate::car::Car;
+use crate::geometry::Vec2;
+use crate::route::Route;
+use std::collections::HashSet;
+
+/// Two car bodies found overlapping at the end of a tick.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Collision {
+    pub tick: u64,
+    pub car_ids: (u32, u32),
+    pub routes: (Route, Route),
+    /// Midpoint between the two cars.
+    pub position: Vec2,
+}
+
+/// Checks every pair of cars for overlapping bodies once per tick,
+/// using their oriented bodies so cars mid-turn are tested at their real angle.
+///
+/// An overlap that lasts several ticks is one collision, reported on the tick
+/// it starts. The same pair can collide again after they have separated.
+#[derive(Default)]
+pub struct CollisionDetector {
+    touching: HashSet<(u32, u32)>,
+}
+
+impl CollisionDetector {
+    /// Return the collisions that start on `tick`.
+    pub fn detect(&mut self, tick: u64, cars: &[Car]) -> Vec<Collision> {
+        let mut touching = HashSet::new();
+        let mut new_collisions = Vec::new();
+
+        for (i, car) in cars.iter().enumerate() {
+            for other in &cars[i + 1..] {
+                if !car.body.intersects(&other.body) {
+                    continue;
+                }
+                let pair = (car.id.min(other.id), car.id.max(other.id));
+                if !self.touching.contains(&pair) {
+                    new_collisions.push(Collision {
+                        tick,
+                        car_ids: (car.id, other.id),
+                        routes: (car.route, other.route),
+                        position: Vec2::new(
+                            (car.body.center.x + other.body.center.x) / 2.0,
+                            (car.body.center.y + other.body.center.y) / 2.0,
+                        ),
+                    });
+                }
+                touching.insert(pair);
+            }
+        }
+
+        self.touching = touching;
+        new_collisions
+    }
+}
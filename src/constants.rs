@@ -1,6 +1,6 @@
-use crate::car::CAR_SIZE;
-
-pub const WINDOW_SIZE: u32 = 800; // Changed back to 800
-pub const LINE_SPACING: i32 = (WINDOW_SIZE / 14) as i32;
-pub const FRAME_DURATION: std::time::Duration = std::time::Duration::from_nanos(1);
-pub const OFFSET: f32 = (LINE_SPACING as f32 / 2.0) - (CAR_SIZE.y / 2.75);
+pub const FRAME_DURATION: std::time::Duration = std::time::Duration::from_millis(1);
+pub const TICK_RATE: u32 = 60; // simulation ticks per simulated second
+pub const TICK_DURATION: f32 = 1.0 / TICK_RATE as f32;
+pub const MAX_FRAME_TIME: f32 = 0.25; // cap on wall-clock time simulated per frame
+pub const MIN_TIME_SCALE: f32 = 0.25; // slowest simulation speed, relative to real time
+pub const MAX_TIME_SCALE: f32 = 16.0; // fastest simulation speed, relative to real time
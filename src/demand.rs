@@ -0,0 +1,277 @@
+use crate::route::{Approach, Route, Turn};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// How arrival times are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Arrivals {
+    /// One car every `spawn_delay` seconds, on any route.
+    #[default]
+    Fixed,
+    /// Independent Poisson arrivals on every approach, at `rates`.
+    Poisson,
+}
+
+/// Mean arrivals on each approach, in cars per minute.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ApproachRates {
+    pub right: f32,
+    pub down: f32,
+    pub left: f32,
+    pub up: f32,
+}
+
+impl Default for ApproachRates {
+    fn default() -> Self {
+        Self {
+            right: 15.0,
+            down: 15.0,
+            left: 15.0,
+            up: 15.0,
+        }
+    }
+}
+
+impl ApproachRates {
+    pub fn get(&self, approach: Approach) -> f32 {
+        match approach {
+            Approach::Right => self.right,
+            Approach::Down => self.down,
+            Approach::Left => self.left,
+            Approach::Up => self.up,
+        }
+    }
+}
+
+/// Demand multiplier from `time` seconds into the run.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfilePoint {
+    pub time: f32,
+    pub factor: f32,
+}
+
+/// How cars are added to the road while random spawning is on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Demand {
+    /// Start with random spawning on, as if `R` had been pressed.
+    pub random_generation: bool,
+    pub arrivals: Arrivals,
+    /// Seconds between spawns with fixed arrivals.
+    pub spawn_delay: f32,
+    /// Arrival rates with Poisson arrivals.
+    pub rates: ApproachRates,
+    /// Origin-destination matrix: relative weight of every route. Routes
+    /// left out never get a car; all routes are equally likely if empty.
+    /// With Poisson arrivals the weights split each approach's arrivals
+    /// between its three routes.
+    pub od: BTreeMap<Route, f32>,
+    /// Time-of-day profile scaling the demand over the run, interpolated
+    /// linearly between points given in order of time. Flat if empty.
+    pub profile: Vec<ProfilePoint>,
+    /// Most cars that may wait off the map to enter a lane; later arrivals
+    /// are turned away. Unlimited if unset.
+    pub max_queue: Option<usize>,
+}
+
+impl Default for Demand {
+    fn default() -> Self {
+        Self {
+            random_generation: false,
+            arrivals: Arrivals::Fixed,
+            spawn_delay: 0.1,
+            rates: ApproachRates::default(),
+            od: BTreeMap::new(),
+            profile: Vec::new(),
+            max_queue: None,
+        }
+    }
+}
+
+impl Demand {
+    /// Demand multiplier `time` seconds into the run.
+    pub fn factor_at(&self, time: f32) -> f32 {
+        let (Some(first), Some(last)) = (self.profile.first(), self.profile.last()) else {
+            return 1.0;
+        };
+        if time <= first.time {
+            return first.factor;
+        }
+        for pair in self.profile.windows(2) {
+            let (from, to) = (pair[0], pair[1]);
+            if time <= to.time {
+                let t = (time - from.time) / (to.time - from.time);
+                return from.factor + (to.factor - from.factor) * t;
+            }
+        }
+        last.factor
+    }
+
+    /// Pick the route of a car arriving on `approach`, or on any approach.
+    /// `None` if the OD matrix gives no route there any weight.
+    fn route(&self, approach: Option<Approach>, rng: &mut impl Rng) -> Option<Route> {
+        if self.od.is_empty() {
+            return Some(match approach {
+                Some(approach) => {
+                    Route::new(approach, Turn::ALL[rng.gen_range(0..Turn::ALL.len())])
+                }
+                None => Route::ALL[rng.gen_range(0..Route::ALL.len())],
+            });
+        }
+        let routes: Vec<Route> = Route::ALL
+            .into_iter()
+            .filter(|route| approach.is_none_or(|approach| route.approach() == approach))
+            .collect();
+        routes
+            .choose_weighted(rng, |route| self.od.get(route).copied().unwrap_or(0.0))
+            .ok()
+            .copied()
+    }
+}
+
+/// Draws the cars arriving at the edge of the map, tick by tick.
+#[derive(Debug, Clone, Default)]
+pub struct DemandGenerator {
+    /// Seconds since the last spawn with fixed arrivals.
+    time_since_spawn: f32,
+}
+
+impl DemandGenerator {
+    /// Start the fixed arrival timer over.
+    pub fn restart(&mut self) {
+        self.time_since_spawn = 0.0;
+    }
+
+    /// Routes of the cars arriving in the `dt` seconds up to `time`.
+    pub fn arrivals(
+        &mut self,
+        demand: &Demand,
+        time: f32,
+        dt: f32,
+        rng: &mut impl Rng,
+    ) -> Vec<Route> {
+        let factor = demand.factor_at(time);
+        match demand.arrivals {
+            Arrivals::Fixed => {
+                self.time_since_spawn += dt;
+                if factor > 0.0 && self.time_since_spawn >= demand.spawn_delay / factor {
+                    self.time_since_spawn = 0.0;
+                    demand.route(None, rng).into_iter().collect()
+                } else {
+                    Vec::new()
+                }
+            }
+            Arrivals::Poisson => {
+                let mut routes = Vec::new();
+                for approach in Approach::ALL {
+                    let mean = demand.rates.get(approach) / 60.0 * factor * dt;
+                    for _ in 0..poisson(mean, rng) {
+                        routes.extend(demand.route(Some(approach), rng));
+                    }
+                }
+                routes
+            }
+        }
+    }
+}
+
+/// Number of events in an interval expecting `mean` of them (Knuth's method,
+/// fine for the small means of a single tick).
+fn poisson(mean: f32, rng: &mut impl Rng) -> u32 {
+    if mean <= 0.0 {
+        return 0;
+    }
+    let limit = (-mean).exp();
+    let mut count = 0;
+    let mut product: f32 = rng.gen();
+    while product > limit {
+        count += 1;
+        product *= rng.gen::<f32>();
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::TICK_DURATION;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// Every arrival of `ticks` ticks of `demand`, from a fixed seed.
+    fn arrivals(demand: &Demand, ticks: u32) -> Vec<Route> {
+        let mut generator = DemandGenerator::default();
+        let mut rng = StdRng::seed_from_u64(1);
+        (1..=ticks)
+            .flat_map(|tick| {
+                let time = tick as f32 * TICK_DURATION;
+                generator.arrivals(demand, time, TICK_DURATION, &mut rng)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn profile_is_interpolated_and_held_at_its_ends() {
+        let point = |time, factor| ProfilePoint { time, factor };
+        let demand = Demand {
+            profile: vec![point(60.0, 1.0), point(120.0, 3.0), point(180.0, 0.5)],
+            ..Demand::default()
+        };
+        assert_eq!(demand.factor_at(0.0), 1.0);
+        assert_eq!(demand.factor_at(60.0), 1.0);
+        assert_eq!(demand.factor_at(90.0), 2.0);
+        assert_eq!(demand.factor_at(120.0), 3.0);
+        assert_eq!(demand.factor_at(150.0), 1.75);
+        assert_eq!(demand.factor_at(180.0), 0.5);
+        assert_eq!(demand.factor_at(1000.0), 0.5);
+
+        assert_eq!(Demand::default().factor_at(42.0), 1.0);
+    }
+
+    #[test]
+    fn poisson_arrivals_average_out_to_the_rates() {
+        let demand = Demand {
+            arrivals: Arrivals::Poisson,
+            rates: ApproachRates {
+                right: 60.0,
+                down: 30.0,
+                left: 60.0,
+                up: 0.0,
+            },
+            ..Demand::default()
+        };
+        // 1000 seconds: 1000, 500, 1000 and 0 cars expected
+        let routes = arrivals(&demand, 60_000);
+        for approach in Approach::ALL {
+            let expected = demand.rates.get(approach) / 60.0 * 1000.0;
+            let count = routes
+                .iter()
+                .filter(|route| route.approach() == approach)
+                .count() as f32;
+            assert!(
+                (count - expected).abs() <= 0.05 * expected,
+                "{count} arrivals from {approach:?}, expected {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn od_with_a_single_route_only_produces_that_route() {
+        let od = BTreeMap::from([(Route::RU, 1.0)]);
+        for arrivals_kind in [Arrivals::Fixed, Arrivals::Poisson] {
+            let demand = Demand {
+                arrivals: arrivals_kind,
+                od: od.clone(),
+                ..Demand::default()
+            };
+            let routes = arrivals(&demand, 6000);
+            assert!(!routes.is_empty());
+            assert!(routes.iter().all(|&route| route == Route::RU));
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use crate::car::Comfort;
+use crate::collision::Collision;
+use crate::metrics::TickSample;
+use crate::route::Route;
+use crate::safety::{CloseCall, CloseCallKind};
+use crate::trip::TripRecord;
+
+/// Something that happened during a simulation tick.
+/// Events of one tick are emitted in the order they happened.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SimEvent {
+    /// A car showed up at the edge of the map and joined the entry queue
+    /// of its lane.
+    Arrived {
+        tick: u64,
+        route: Route,
+    },
+    /// A car made it onto the map, after waiting in the entry queue since
+    /// `arrival_tick` if that's earlier.
+    Spawned {
+        tick: u64,
+        car_id: u32,
+        route: Route,
+        arrival_tick: u64,
+    },
+    /// The entry queue of the lane was full, so the car that arrived last
+    /// gave up.
+    TurnedAway {
+        tick: u64,
+        route: Route,
+    },
+    /// A car first touched the core intersection.
+    EnteredIntersection {
+        tick: u64,
+        car_id: u32,
+    },
+    /// A car was clear of the core intersection again.
+    ExitedIntersection {
+        tick: u64,
+        car_id: u32,
+    },
+    /// A turning car finished its turn and drives along its exit lane.
+    Turned {
+        tick: u64,
+        car_id: u32,
+        route: Route,
+    },
+    /// A car slowed below `trip::WAITING_SPEED`.
+    StartedWaiting {
+        tick: u64,
+        car_id: u32,
+    },
+    /// A car got going again after waiting for `waited` seconds.
+    StoppedWaiting {
+        tick: u64,
+        car_id: u32,
+        waited: f32,
+    },
+    CloseCall(CloseCall),
+    Collision(Collision),
+    /// A car drove off the map, with how its trip went.
+    Despawned {
+        trip: TripRecord,
+        comfort: Comfort,
+    },
+    /// The tick is over, leaving the road as in the sample.
+    TickEnded(TickSample),
+}
+
+/// Receives every event of a simulation it was registered with through
+/// `Simulation::subscribe`.
+pub trait Subscriber {
+    fn on_event(&mut self, event: &SimEvent);
+}
+
+/// Prints collisions and close calls to the terminal.
+pub struct ConsoleLog;
+
+impl Subscriber for ConsoleLog {
+    fn on_event(&mut self, event: &SimEvent) {
+        match event {
+            SimEvent::Collision(collision) => println!(
+                "Collision at tick {}: car {} ({}) and car {} ({}) at ({:.0}, {:.0})",
+                collision.tick,
+                collision.car_ids.0,
+                collision.routes.0,
+                collision.car_ids.1,
+                collision.routes.1,
+                collision.position.x,
+                collision.position.y,
+            ),
+            SimEvent::CloseCall(close_call) => {
+                let measure = match close_call.kind {
+                    CloseCallKind::TimeToCollision(ttc) => format!("TTC {ttc:.2}s"),
+                    CloseCallKind::PostEncroachmentTime(pet) => format!("PET {pet:.2}s"),
+                };
+                println!(
+                    "Close call at tick {}: car {} ({}) and car {} ({}), {}",
+                    close_call.tick,
+                    close_call.car_ids.0,
+                    close_call.routes.0,
+                    close_call.car_ids.1,
+                    close_call.routes.1,
+                    measure,
+                );
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use crate::cli::OutputArgs;
+use crate::metrics::Metrics;
+use crate::simulation::Simulation;
+use crate::stats::Summary;
+use crate::trip::TripRecord;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::fs::{self, File};
+use std::path::Path;
+
+/// File formats selectable with `--format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// One table per file, with a header row
+    Csv,
+    /// One array of objects per file
+    Json,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+/// Aggregate results of a run, flattened to a single row.
+/// Distribution fields are empty when no car completed its journey.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunSummary {
+    pub seed: u64,
+    pub policy: String,
+    pub cars_arrived: u32,
+    pub cars_spawned: u32,
+    pub cars_completed: u32,
+    pub spawns_delayed: u32,
+    pub spawns_rejected: u32,
+    pub entry_queue_at_end: u32,
+    pub entry_delay_mean: Option<f32>,
+    pub entry_delay_p95: Option<f32>,
+    pub collisions: u32,
+    pub close_calls: u32,
+    pub min_speed: Option<f32>,
+    pub max_speed: Option<f32>,
+    pub trip_time_mean: Option<f32>,
+    pub trip_time_median: Option<f32>,
+    pub trip_time_p95: Option<f32>,
+    pub wait_time_mean: Option<f32>,
+    pub wait_time_median: Option<f32>,
+    pub wait_time_p95: Option<f32>,
+    pub average_speed_mean: Option<f32>,
+    pub peak_braking_mean: Option<f32>,
+    pub peak_braking_p95: Option<f32>,
+    pub mean_abs_jerk_mean: Option<f32>,
+    pub hard_braking_events: u32,
+}
+
+impl RunSummary {
+    pub fn new(seed: u64, policy: &str, metrics: &Metrics) -> Self {
+        let summarize = |samples: Vec<f32>| Summary::of(&samples);
+        let trip_time = summarize(metrics.trips.iter().map(TripRecord::trip_time).collect());
+        let wait_time = summarize(metrics.trips.iter().map(|trip| trip.wait_time).collect());
+        let average_speed = summarize(
+            metrics
+                .trips
+                .iter()
+                .map(|trip| trip.average_speed)
+                .collect(),
+        );
+        let peak_braking = Summary::of(&metrics.peak_decelerations);
+        let jerk = Summary::of(&metrics.mean_abs_jerks);
+        let entry_delay = Summary::of(&metrics.entry_delays);
+        let speed_seen = metrics.max_vehicle_speed > 0.0;
+
+        Self {
+            seed,
+            policy: policy.to_string(),
+            cars_arrived: metrics.cars_arrived,
+            cars_spawned: metrics.cars_spawned,
+            cars_completed: metrics.trips.len() as u32,
+            spawns_delayed: metrics.spawns_delayed,
+            spawns_rejected: metrics.spawns_rejected,
+            entry_queue_at_end: metrics.entry_queue_at_end(),
+            entry_delay_mean: entry_delay.map(|s| s.mean),
+            entry_delay_p95: entry_delay.map(|s| s.p95),
+            collisions: metrics.collision_count,
+            close_calls: metrics.close_call_count,
+            min_speed: speed_seen.then_some(metrics.min_vehicle_speed),
+            max_speed: speed_seen.then_some(metrics.max_vehicle_speed),
+            trip_time_mean: trip_time.map(|s| s.mean),
+            trip_time_median: trip_time.map(|s| s.median),
+            trip_time_p95: trip_time.map(|s| s.p95),
+            wait_time_mean: wait_time.map(|s| s.mean),
+            wait_time_median: wait_time.map(|s| s.median),
+            wait_time_p95: wait_time.map(|s| s.p95),
+            average_speed_mean: average_speed.map(|s| s.mean),
+            peak_braking_mean: peak_braking.map(|s| s.mean),
+            peak_braking_p95: peak_braking.map(|s| s.p95),
+            mean_abs_jerk_mean: jerk.map(|s| s.mean),
+            hard_braking_events: metrics.hard_braking_events.iter().sum(),
+        }
+    }
+}
+
+/// Write `summary`, `trips` and `ticks` files for the run into `directory`,
+/// creating it if needed.
+pub fn export_run(
+    directory: &Path,
+    format: ExportFormat,
+    seed: u64,
+    policy: &str,
+    metrics: &Metrics,
+) -> Result<(), String> {
+    fs::create_dir_all(directory)
+        .map_err(|e| format!("Failed to create {}: {e}", directory.display()))?;
+
+    let summary = [RunSummary::new(seed, policy, metrics)];
+    write_table(directory, "summary", format, &summary)?;
+    write_table(directory, "trips", format, &metrics.trips)?;
+    write_table(directory, "ticks", format, &metrics.ticks)
+}
+
+/// Write the summaries of several runs into one `summary` file in `directory`,
+/// creating it if needed.
+pub fn export_summaries(
+    directory: &Path,
+    format: ExportFormat,
+    summaries: &[RunSummary],
+) -> Result<(), String> {
+    fs::create_dir_all(directory)
+        .map_err(|e| format!("Failed to create {}: {e}", directory.display()))?;
+    write_table(directory, "summary", format, summaries)
+}
+
+/// Export `simulation` into the directory named by `--output`, if any.
+pub fn export_simulation(output: &OutputArgs, simulation: &Simulation) -> Result<(), String> {
+    let Some(directory) = &output.output else {
+        return Ok(());
+    };
+    export_run(
+        directory,
+        output.format,
+        simulation.seed(),
+        simulation.policy_name(),
+        simulation.metrics(),
+    )?;
+    println!("Exported run to {}", directory.display());
+    Ok(())
+}
+
+/// Write `rows` to `directory/name.<extension>`.
+pub fn write_table<T: Serialize>(
+    directory: &Path,
+    name: &str,
+    format: ExportFormat,
+    rows: &[T],
+) -> Result<(), String> {
+    let path = directory.join(name).with_extension(format.extension());
+    let file =
+        File::create(&path).map_err(|e| format!("Failed to create {}: {e}", path.display()))?;
+
+    match format {
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(file);
+            for row in rows {
+                writer
+                    .serialize(row)
+                    .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+            }
+            writer
+                .flush()
+                .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+        }
+        ExportFormat::Json => serde_json::to_writer_pretty(file, rows)
+            .map_err(|e| format!("Failed to write {}: {e}", path.display())),
+    }
+}
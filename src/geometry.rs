@@ -0,0 +1,231 @@
+/// A simple 2D vector for float values
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vec2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Vec2 {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+    pub fn distance(&self, other: Vec2) -> f32 {
+        let dx = other.x - self.x;
+        let dy = other.y - self.y;
+        (dx * dx + dy * dy).sqrt()
+    }
+    pub fn dot(&self, other: Vec2) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
+    /// Unit vector pointing at `angle` radians (0 is East, PI/2 is South).
+    pub fn from_angle(angle: f32) -> Self {
+        Self::new(angle.cos(), angle.sin())
+    }
+}
+
+/// A float-based rectangle for collision/drawing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FRect {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+impl FRect {
+    pub fn new(x: f32, y: f32, w: f32, h: f32) -> Self {
+        Self { x, y, w, h }
+    }
+
+    /// Returns `Some(intersection)` if `self` intersects `other`, else `None`.
+    pub fn intersect(&self, other: FRect) -> Option<FRect> {
+        let rx = self.x.max(other.x);
+        let ry = self.y.max(other.y);
+        let rw = (self.x + self.w).min(other.x + other.w) - rx;
+        let rh = (self.y + self.h).min(other.y + other.h) - ry;
+        if rw > 0.0 && rh > 0.0 {
+            Some(FRect::new(rx, ry, rw, rh))
+        } else {
+            None
+        }
+    }
+}
+
+/// A rectangle rotated by `angle` radians around its center.
+///
+/// `size.x` runs along the angle and `size.y` across it, so a car body is
+/// `OrientedRect::new(center, car_size.size(), heading)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrientedRect {
+    pub center: Vec2,
+    pub size: Vec2,
+    pub angle: f32,
+}
+
+impl OrientedRect {
+    pub fn new(center: Vec2, size: Vec2, angle: f32) -> Self {
+        Self {
+            center,
+            size,
+            angle,
+        }
+    }
+
+    /// Unit vectors along the length and across the width.
+    fn axes(&self) -> [Vec2; 2] {
+        let along = Vec2::from_angle(self.angle);
+        [along, Vec2::new(-along.y, along.x)]
+    }
+
+    /// Corners in order around the rectangle.
+    pub fn corners(&self) -> [Vec2; 4] {
+        let [along, across] = self.axes();
+        let (hx, hy) = (self.size.x / 2.0, self.size.y / 2.0);
+        let corner = |a: f32, b: f32| {
+            Vec2::new(
+                self.center.x + along.x * a + across.x * b,
+                self.center.y + along.y * a + across.y * b,
+            )
+        };
+        [
+            corner(hx, hy),
+            corner(-hx, hy),
+            corner(-hx, -hy),
+            corner(hx, -hy),
+        ]
+    }
+
+    /// Separating-axis test. Rectangles that only touch along an edge
+    /// don't intersect, same as `FRect::intersect`.
+    pub fn intersects(&self, other: &OrientedRect) -> bool {
+        let (corners_a, corners_b) = (self.corners(), other.corners());
+        self.axes().iter().chain(other.axes().iter()).all(|&axis| {
+            let (min_a, max_a) = project(&corners_a, axis);
+            let (min_b, max_b) = project(&corners_b, axis);
+            max_a > min_b && max_b > min_a
+        })
+    }
+
+    /// Shortest distance between the two rectangles, or 0 if they intersect.
+    pub fn distance(&self, other: &OrientedRect) -> f32 {
+        if self.intersects(other) {
+            return 0.0;
+        }
+        // Between two disjoint convex shapes the closest points include
+        // a corner of one of them
+        let corners_to_edges = |from: &OrientedRect, to: &OrientedRect| {
+            let edges = to.corners();
+            from.corners()
+                .iter()
+                .flat_map(|&corner| {
+                    (0..4).map(move |i| point_to_segment(corner, edges[i], edges[(i + 1) % 4]))
+                })
+                .fold(f32::MAX, f32::min)
+        };
+        corners_to_edges(self, other).min(corners_to_edges(other, self))
+    }
+
+    /// Smallest axis-aligned rectangle containing this one.
+    pub fn bounding_box(&self) -> FRect {
+        let corners = self.corners();
+        let (min_x, max_x) = corners.iter().fold((f32::MAX, f32::MIN), |(min, max), c| {
+            (min.min(c.x), max.max(c.x))
+        });
+        let (min_y, max_y) = corners.iter().fold((f32::MAX, f32::MIN), |(min, max), c| {
+            (min.min(c.y), max.max(c.y))
+        });
+        FRect::new(min_x, min_y, max_x - min_x, max_y - min_y)
+    }
+}
+
+impl From<FRect> for OrientedRect {
+    fn from(rect: FRect) -> Self {
+        OrientedRect::new(
+            Vec2::new(rect.x + rect.w / 2.0, rect.y + rect.h / 2.0),
+            Vec2::new(rect.w, rect.h),
+            0.0,
+        )
+    }
+}
+
+/// Range covered by `corners` when projected onto `axis`.
+fn project(corners: &[Vec2; 4], axis: Vec2) -> (f32, f32) {
+    corners
+        .iter()
+        .map(|corner| corner.dot(axis))
+        .fold((f32::MAX, f32::MIN), |(min, max), p| {
+            (min.min(p), max.max(p))
+        })
+}
+
+/// Distance from `point` to the segment between `a` and `b`.
+fn point_to_segment(point: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = Vec2::new(b.x - a.x, b.y - a.y);
+    let ap = Vec2::new(point.x - a.x, point.y - a.y);
+    let length_squared = ab.dot(ab);
+    let t = if length_squared > 0.0 {
+        (ap.dot(ab) / length_squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    point.distance(Vec2::new(a.x + ab.x * t, a.y + ab.y * t))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::{FRAC_PI_4, SQRT_2};
+
+    fn square(center: Vec2, angle: f32) -> OrientedRect {
+        OrientedRect::new(center, Vec2::new(10.0, 10.0), angle)
+    }
+
+    #[test]
+    fn rotated_corner_overlaps() {
+        // The diamond's left corner reaches 12 - 5√2 ≈ 4.93, inside the square
+        let a = square(Vec2::new(0.0, 0.0), 0.0);
+        let b = square(Vec2::new(12.0, 0.0), FRAC_PI_4);
+        assert!(a.intersects(&b));
+        assert!(b.intersects(&a));
+        assert_eq!(a.distance(&b), 0.0);
+    }
+
+    #[test]
+    fn near_miss_at_45_degrees() {
+        // Bounding boxes overlap, but the diamond's edge stays 4√2 - 5
+        // away from the square's corner along the diagonal
+        let a = square(Vec2::new(0.0, 0.0), 0.0);
+        let b = square(Vec2::new(9.0, 9.0), FRAC_PI_4);
+        assert!(a.bounding_box().intersect(b.bounding_box()).is_some());
+        assert!(!a.intersects(&b));
+        assert!(!b.intersects(&a));
+        assert!((a.distance(&b) - (4.0 * SQRT_2 - 5.0)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn touching_edges_do_not_intersect() {
+        let a = OrientedRect::from(FRect::new(0.0, 0.0, 10.0, 10.0));
+        let side = OrientedRect::from(FRect::new(10.0, 0.0, 10.0, 10.0));
+        let corner = OrientedRect::from(FRect::new(10.0, 10.0, 10.0, 10.0));
+        assert!(!a.intersects(&side));
+        assert!(!a.intersects(&corner));
+        assert_eq!(a.distance(&side), 0.0);
+        assert_eq!(a.distance(&corner), 0.0);
+    }
+
+    #[test]
+    fn distance_between_rotated_rects() {
+        // Two diamonds side by side, facing corner to corner
+        let a = square(Vec2::new(0.0, 0.0), FRAC_PI_4);
+        let b = square(Vec2::new(20.0, 0.0), -FRAC_PI_4);
+        assert!(!a.intersects(&b));
+        assert!((a.distance(&b) - (20.0 - 10.0 * SQRT_2)).abs() < 1e-3);
+        assert!((b.distance(&a) - a.distance(&b)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn frect_round_trips_through_bounding_box() {
+        let rect = FRect::new(3.0, 4.0, 10.0, 6.0);
+        assert_eq!(OrientedRect::from(rect).bounding_box(), rect);
+    }
+}
@@ -0,0 +1,56 @@
+use crate::cli::{HeadlessArgs, ReportArgs};
+use crate::constants::TICK_DURATION;
+use crate::event::ConsoleLog;
+use crate::export;
+use crate::record::Recording;
+use crate::scenario::Scenario;
+use crate::simulation::Simulation;
+
+/// Step `simulation` for `ticks` ticks, turning random spawning on first
+/// since nobody is there to press `R`.
+pub fn simulate(simulation: &mut Simulation, ticks: u64) {
+    if !simulation.is_random_generation() {
+        simulation.toggle_random_generation();
+    }
+    for _ in 0..ticks {
+        simulation.step(TICK_DURATION);
+    }
+}
+
+/// Print the metrics screen and the trip report of `simulation` to the terminal.
+fn print_report(simulation: &Simulation) {
+    let metrics = simulation.metrics();
+    let (_, stats) = metrics.display(simulation.seed(), simulation.policy_name());
+    for line in stats.iter().chain(&metrics.trip_report()) {
+        println!("{line}");
+    }
+}
+
+/// The `headless` subcommand.
+pub fn run(args: &HeadlessArgs) -> Result<(), String> {
+    let scenario = Scenario::from_args(&args.scenario)?;
+    let seed = args.seed.unwrap_or_else(rand::random);
+    println!("Seed: {seed}");
+
+    let mut simulation = Simulation::new(seed, scenario);
+    simulation.subscribe(Box::new(ConsoleLog));
+    simulate(&mut simulation, args.ticks);
+
+    print_report(&simulation);
+    export::export_simulation(&args.output, &simulation)
+}
+
+/// The `report` subcommand.
+pub fn report(args: &ReportArgs) -> Result<(), String> {
+    let recording = Recording::load(&args.file)?;
+    println!(
+        "Re-running seed {} for {} ticks",
+        recording.seed, recording.ticks
+    );
+
+    let mut simulation = Simulation::new(recording.seed, recording.scenario.clone());
+    recording.play(&mut simulation, recording.ticks);
+
+    print_report(&simulation);
+    export::export_simulation(&args.output, &simulation)
+}
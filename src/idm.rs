@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+/// Intelligent Driver Model parameters for one driver.
+///
+/// Lengths are in pixels and times in seconds. A car is 43 px long,
+/// so roughly 10 px make a meter.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IdmParams {
+    /// Cruising speed on an open road, in px/s.
+    pub desired_speed: f32,
+    /// Time gap kept to the car in front, in seconds.
+    pub time_headway: f32,
+    /// Bumper-to-bumper gap kept when standing still, in px.
+    pub min_gap: f32,
+    /// Acceleration from a standstill, in px/s².
+    pub max_acceleration: f32,
+    /// Braking the driver is comfortable with, in px/s².
+    pub comfortable_deceleration: f32,
+    /// Hardest the car can brake in an emergency, in px/s².
+    pub max_deceleration: f32,
+}
+
+impl Default for IdmParams {
+    fn default() -> Self {
+        Self {
+            desired_speed: 120.0,
+            time_headway: 1.0,
+            min_gap: 6.0,
+            max_acceleration: 60.0,
+            comfortable_deceleration: 90.0,
+            max_deceleration: 270.0,
+        }
+    }
+}
+
+impl IdmParams {
+    /// Acceleration of a car driving at `speed`, with `leader` holding the
+    /// gap to whatever is in front and how fast it's moving our way,
+    /// or `None` on an open road. Can be far below `-comfortable_deceleration`
+    /// when the gap closes faster than planned, and even below
+    /// `-max_deceleration`: the brakes are the car's business, not the model's.
+    pub fn acceleration(&self, speed: f32, leader: Option<(f32, f32)>) -> f32 {
+        let free_road = 1.0 - (speed / self.desired_speed).powi(4);
+
+        let interaction = match leader {
+            Some((gap, leader_speed)) => {
+                let braking_term = speed * (speed - leader_speed)
+                    / (2.0 * (self.max_acceleration * self.comfortable_deceleration).sqrt());
+                let desired_gap =
+                    self.min_gap + (speed * self.time_headway + braking_term).max(0.0);
+                (desired_gap / gap.max(f32::EPSILON)).powi(2)
+            }
+            None => 0.0,
+        };
+
+        self.max_acceleration * (free_road - interaction)
+    }
+}
@@ -1,22 +1,72 @@
+mod batch;
 mod car;
+mod cli;
+mod collision;
 mod constants;
+mod demand;
+mod event;
+mod export;
+mod geometry;
+mod headless;
+mod idm;
 mod image;
 mod metrics;
+mod path;
+mod policy;
+mod record;
+mod replay;
+mod reservation;
+mod route;
+mod safety;
+mod scenario;
+mod signal;
+mod simulation;
+mod stats;
 mod text;
-use crate::car::{check_perpendicular_and_move_back, Car, FRect, Vec2}; // Add FRect and Vec2 imports
+mod trip;
+use car::{outline, Car};
+use clap::Parser;
+use cli::{Cli, Command, ReplayArgs, RunArgs};
 use constants::*;
+use event::{ConsoleLog, SimEvent};
+use geometry::{FRect, OrientedRect, Vec2};
 use image::draw_image;
-use metrics::*; // Changed to import all metrics functions
-use rand::Rng;
+use record::{Recorder, Recording};
+use route::{Approach, Route};
+use scenario::{Layout, Scenario};
 use sdl2::event::Event;
 use sdl2::image::LoadTexture;
 use sdl2::keyboard::Keycode;
 use sdl2::pixels::Color;
-use std::time::Duration;
+use signal::SignalColor;
+use simulation::{SimInput, Simulation};
 use std::time::Instant;
 use text::draw_text;
 
 pub fn main() {
+    let cli = Cli::parse();
+    let result = match &cli.command {
+        Command::Run(args) => run(args),
+        Command::Headless(args) => headless::run(args),
+        Command::Batch(args) => batch::batch(args),
+        Command::Replay(args) => replay(args),
+        Command::Report(args) => headless::report(args),
+    };
+    if let Err(e) = result {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
+
+/// SDL2 window of `size` by `size` pixels, with the event pump and font
+/// context to go with it.
+fn open_window(
+    size: u32,
+) -> (
+    sdl2::render::Canvas<sdl2::video::Window>,
+    sdl2::EventPump,
+    sdl2::ttf::Sdl2TtfContext,
+) {
     let sdl_context = sdl2::init().expect("Failed to initialize SDL2");
     let video_subsystem = sdl_context
         .video()
@@ -24,78 +74,82 @@ pub fn main() {
     let ttf_context = sdl2::ttf::init().expect("Failed to initialize SDL2 TTF");
 
     let window = video_subsystem
-        .window("Smart Road", WINDOW_SIZE, WINDOW_SIZE)
+        .window("Smart Road", size, size)
         .position_centered()
         .build()
         .expect("Failed to create window");
 
-    let mut canvas = window
+    let canvas = window
         .into_canvas()
         .build()
         .expect("Failed to create canvas");
 
-    let mut event_pump = sdl_context
+    let event_pump = sdl_context
         .event_pump()
         .expect("Failed to get SDL2 event pump");
 
-    render_simulation(&mut canvas, &mut event_pump);
-
-    render_metrics(&mut canvas, &mut event_pump, &ttf_context);
-}
-
-fn spawn_car_with_direction(cars: &mut Vec<Car>, next_id: u32, behavior: &str, direction: &str) {
-    Car::spawn_if_can(cars, next_id, behavior, direction);
+    (canvas, event_pump, ttf_context)
 }
 
-fn spawn_random_car(cars: &mut Vec<Car>, next_id: u32) {
-    let mut rng = rand::thread_rng();
-    let behaviors = [
-        ("RU", "West"),
-        ("RL", "West"),
-        ("RD", "West"),
-        ("DU", "North"),
-        ("DL", "North"),
-        ("DR", "North"),
-        ("LU", "East"),
-        ("LR", "East"),
-        ("LD", "East"),
-        ("UD", "South"),
-        ("UR", "South"),
-        ("UL", "South"),
-    ];
-    let (behavior, direction) = behaviors[rng.gen_range(0..behaviors.len())];
-    Car::spawn_if_can(cars, next_id, behavior, direction);
-}
+/// The `run` subcommand.
+fn run(args: &RunArgs) -> Result<(), String> {
+    let scenario = Scenario::from_args(&args.scenario)?;
+    let seed = args.seed.unwrap_or_else(rand::random);
+    println!("Seed: {seed}");
+    let (mut canvas, mut event_pump, ttf_context) = open_window(scenario.layout.window_size);
+
+    let mut recorder = args
+        .record
+        .is_some()
+        .then(|| Recorder::new(seed, scenario.clone(), args.record_snapshots));
+    let mut simulation = Simulation::new(seed, scenario);
+    simulation.subscribe(Box::new(ConsoleLog));
+    render_simulation(
+        &mut canvas,
+        &mut event_pump,
+        &ttf_context,
+        &mut simulation,
+        recorder.as_mut(),
+        args.pause_on_collision,
+    );
 
-fn get_random_behavior_for_direction(direction: &str) -> &'static str {
-    let mut rng = rand::thread_rng();
-    match direction {
-        "West" => {
-            let behaviors = ["RU", "RL", "RD"];
-            behaviors[rng.gen_range(0..behaviors.len())]
-        }
-        "North" => {
-            let behaviors = ["DU", "DL", "DR"];
-            behaviors[rng.gen_range(0..behaviors.len())]
-        }
-        "East" => {
-            let behaviors = ["LU", "LR", "LD"];
-            behaviors[rng.gen_range(0..behaviors.len())]
+    if let (Some(path), Some(recorder)) = (&args.record, recorder) {
+        match recorder.finish().save(path) {
+            Ok(()) => println!("Recorded run to {}", path.display()),
+            Err(e) => eprintln!("{e}"),
         }
-        "South" => {
-            let behaviors = ["UD", "UR", "UL"];
-            behaviors[rng.gen_range(0..behaviors.len())]
-        }
-        _ => panic!("Invalid direction"),
     }
+
+    for line in simulation.metrics().trip_report() {
+        println!("{line}");
+    }
+    if let Err(e) = export::export_simulation(&args.output, &simulation) {
+        eprintln!("{e}");
+    }
+    render_metrics(&mut canvas, &mut event_pump, &ttf_context, &simulation);
+    Ok(())
+}
+
+/// The `replay` subcommand.
+fn replay(args: &ReplayArgs) -> Result<(), String> {
+    let recording = Recording::load(&args.file)?;
+    println!("Replaying seed {}", recording.seed);
+    let (mut canvas, mut event_pump, ttf_context) =
+        open_window(recording.scenario.layout.window_size);
+    replay::render_replay(&mut canvas, &mut event_pump, &ttf_context, &recording);
+    Ok(())
 }
+
 // XXX debug, remove at submission
-fn draw_intersection_bounds(canvas: &mut sdl2::render::Canvas<sdl2::video::Window>) {
+fn draw_intersection_bounds(
+    canvas: &mut sdl2::render::Canvas<sdl2::video::Window>,
+    core_intersection: FRect,
+) {
     let intersection_rect = sdl2::rect::Rect::new(
-        (4 * LINE_SPACING) as i32,
-        (4 * LINE_SPACING) as i32,
-        (6 * LINE_SPACING) as u32,
-        (6 * LINE_SPACING) as u32,
+        core_intersection.x as i32,
+        core_intersection.y as i32,
+        core_intersection.w as u32,
+        core_intersection.h as u32,
     );
     canvas.set_draw_color(Color::RGB(255, 0, 0));
     canvas.draw_rect(intersection_rect).unwrap();
@@ -104,13 +158,21 @@ fn draw_intersection_bounds(canvas: &mut sdl2::render::Canvas<sdl2::video::Windo
 fn render_simulation(
     canvas: &mut sdl2::render::Canvas<sdl2::video::Window>,
     event_pump: &mut sdl2::EventPump,
+    ttf_context: &sdl2::ttf::Sdl2TtfContext,
+    simulation: &mut Simulation,
+    mut recorder: Option<&mut Recorder>,
+    pause_on_collision: bool,
 ) {
     // --- 1) Variables that must persist across frames ---
-    let mut is_random_generation = false;
-    let mut next_id: u32 = 0;
-    let mut cars: Vec<Car> = Vec::new();
-    let mut last_spawn_time = Instant::now();
-    let spawn_delay = Duration::from_millis(100);
+    let mut last_frame_time = Instant::now();
+    let mut accumulator = 0.0;
+    let mut paused = false;
+    // Ticks to run while paused, one per press of '.'
+    let mut steps_requested = 0;
+    // Simulated seconds per real second
+    let mut time_scale: f32 = 1.0;
+    // Cars in the collision we paused on, highlighted until resumed
+    let mut collided: Vec<u32> = Vec::new();
 
     // Create texture for cars
     let texture_creator = canvas.texture_creator();
@@ -118,21 +180,13 @@ fn render_simulation(
         .load_texture("assets/car.png")
         .expect("Could not load car texture");
 
-    // Define intersection area
-    let core_intersection = FRect::new(
-        (4 * LINE_SPACING) as f32,
-        (4 * LINE_SPACING) as f32,
-        (6 * LINE_SPACING) as f32,
-        (6 * LINE_SPACING) as f32,
-    );
-
     // --- 2) Main game loop ---
     'simulation_loop: loop {
         // ---------------------------------------
         // A) Handle events (keyboard, quit, etc.)
         // ---------------------------------------
         for event in event_pump.poll_iter() {
-            match event {
+            let input = match event {
                 // Quit or Escape
                 Event::Quit { .. }
                 | Event::KeyDown {
@@ -146,154 +200,172 @@ fn render_simulation(
                 Event::KeyDown {
                     keycode: Some(Keycode::Right),
                     ..
-                } => {
-                    let behavior = get_random_behavior_for_direction("West");
-                    spawn_car_with_direction(&mut cars, next_id, behavior, "West");
-                    next_id += 1;
-                }
+                } => SimInput::SpawnFrom(Approach::Right),
                 Event::KeyDown {
                     keycode: Some(Keycode::Left),
                     ..
-                } => {
-                    let behavior = get_random_behavior_for_direction("East");
-                    spawn_car_with_direction(&mut cars, next_id, behavior, "East");
-                    next_id += 1;
-                }
+                } => SimInput::SpawnFrom(Approach::Left),
                 Event::KeyDown {
                     keycode: Some(Keycode::Up),
                     ..
+                } => SimInput::SpawnFrom(Approach::Up),
+                Event::KeyDown {
+                    keycode: Some(Keycode::Down),
+                    ..
+                } => SimInput::SpawnFrom(Approach::Down),
+
+                // Toggle random generation with 'R'
+                Event::KeyDown {
+                    keycode: Some(Keycode::R),
+                    ..
+                } => SimInput::ToggleRandomGeneration,
+
+                // Pause and resume with Space
+                Event::KeyDown {
+                    keycode: Some(Keycode::Space),
+                    ..
                 } => {
-                    let behavior = get_random_behavior_for_direction("South");
-                    spawn_car_with_direction(&mut cars, next_id, behavior, "South");
-                    next_id += 1;
+                    paused = !paused;
+                    if !paused {
+                        collided.clear();
+                    }
+                    continue;
                 }
+
+                // Step one tick with '.', pausing first if needed
                 Event::KeyDown {
-                    keycode: Some(Keycode::Down),
+                    keycode: Some(Keycode::Period),
                     ..
                 } => {
-                    let behavior = get_random_behavior_for_direction("North");
-                    spawn_car_with_direction(&mut cars, next_id, behavior, "North");
-                    next_id += 1;
+                    paused = true;
+                    steps_requested += 1;
+                    continue;
                 }
 
-                // Toggle random generation with 'R'
+                // Speed up or slow down with '+' and '-'
                 Event::KeyDown {
-                    keycode: Some(Keycode::R),
+                    keycode: Some(Keycode::Plus | Keycode::Equals | Keycode::KpPlus),
                     ..
                 } => {
-                    is_random_generation = !is_random_generation;
-                    last_spawn_time = Instant::now();
+                    time_scale = (time_scale * 2.0).min(MAX_TIME_SCALE);
+                    continue;
                 }
-                _ => {}
+                Event::KeyDown {
+                    keycode: Some(Keycode::Minus | Keycode::KpMinus),
+                    ..
+                } => {
+                    time_scale = (time_scale / 2.0).max(MIN_TIME_SCALE);
+                    continue;
+                }
+                _ => continue,
+            };
+            if let Some(recorder) = recorder.as_deref_mut() {
+                recorder.input(simulation, input);
             }
+            simulation.apply(input);
         }
 
         // ---------------------------------------
-        // B) Random car spawning on a timer
+        // B) Advance the simulation in fixed ticks, `time_scale` times
+        //    faster than real time
         // ---------------------------------------
-        if is_random_generation && last_spawn_time.elapsed() >= spawn_delay {
-            spawn_random_car(&mut cars, next_id);
-            next_id += 1;
-            last_spawn_time = Instant::now();
-        }
-
-        // ---------------------------------------
-        // C) Clear the screen and draw the grid
-        // ---------------------------------------
-        canvas.set_draw_color(Color::RGB(0, 0, 0));
-        canvas.clear();
-        draw_lines(canvas);
-        draw_intersection_bounds(canvas); // optional debug intersection
-
-        // Let each car set waiting flags, if needed
-        {
-            let temp_cars = cars.clone();
-            for car in cars.iter_mut() {
-                car.communicate_with_intersection(&temp_cars, &core_intersection);
+        let elapsed = last_frame_time.elapsed().as_secs_f32().min(MAX_FRAME_TIME);
+        last_frame_time = Instant::now();
+        let mut ticks_due = 0;
+        if paused {
+            // Don't replay the time spent paused once resumed
+            accumulator = 0.0;
+            ticks_due = std::mem::take(&mut steps_requested);
+        } else {
+            accumulator += elapsed * time_scale;
+            while accumulator >= TICK_DURATION {
+                accumulator -= TICK_DURATION;
+                ticks_due += 1;
             }
         }
+        for _ in 0..ticks_due {
+            simulation.step(TICK_DURATION);
+            if let Some(recorder) = recorder.as_deref_mut() {
+                recorder.tick(simulation);
+            }
 
-        // ---------------------------------------
-        // D) First pass: Radar & Speed updates
-        // ---------------------------------------
-        {
-            let temp_cars = cars.clone();
-            for i in 0..cars.len() {
-                let previous_speed = cars[i].current_speed;
-
-                // 1) Radar + speed
-                cars[i].adjust_current_speed();
-                cars[i].update_radar(i, &temp_cars);
-
-                // 2) "Close call" detection
-                if (previous_speed > 0.0 && cars[i].current_speed == 0.0)
-                    || (previous_speed > cars[i].current_speed * 2.0)
-                {
-                    increment_close_call_count();
+            if pause_on_collision {
+                let before = collided.len();
+                for event in simulation.events() {
+                    if let SimEvent::Collision(collision) = event {
+                        collided.extend([collision.car_ids.0, collision.car_ids.1]);
+                    }
                 }
-
-                // 3) Turning logic
-                cars[i].turn_if_can(&temp_cars);
-
-                // 4) Track speed in metrics
-                update_vehicle_speed(cars[i].current_speed);
-                // 5) Check for perpendicular cars with speed 0 and move one back
-                for j in (i + 1)..cars.len() {
-                    check_perpendicular_and_move_back(&mut cars, i, j);
+                if collided.len() > before {
+                    paused = true;
+                    break;
                 }
             }
         }
 
         // ---------------------------------------
-        // E) Second pass: Move each car exactly once
+        // C) Clear the screen and draw the grid
         // ---------------------------------------
-        {
-            let mut temp_cars = cars.clone();
-            for i in 0..cars.len() {
-                // Only move if not waiting
-                if !cars[i].waiting_flag {
-                    cars[i].move_one_step_if_no_collide(&mut temp_cars, &core_intersection);
-                }
-            }
-        }
+        canvas.set_draw_color(Color::RGB(0, 0, 0));
+        canvas.clear();
+        draw_lines(canvas, &simulation.scenario().layout);
+        draw_signal_heads(canvas, simulation);
+        draw_intersection_bounds(canvas, simulation.core_intersection()); // optional debug intersection
 
         // ---------------------------------------
-        // F) Draw all cars
+        // D) Draw all cars
         // ---------------------------------------
         let debug = cfg!(debug_assertions);
-        for car in &cars {
+        for car in simulation.cars() {
             car.draw_all_components(canvas, &car_texture, debug)
                 .expect("Failed to draw car");
         }
+        for car in simulation
+            .cars()
+            .iter()
+            .filter(|car| collided.contains(&car.id))
+        {
+            draw_collision_highlight(canvas, car);
+        }
         // Optionally draw intersection bounds again
-        draw_intersection_bounds(canvas);
+        draw_intersection_bounds(canvas, simulation.core_intersection());
 
         // ---------------------------------------
-        // G) Remove cars that have reached destination
+        // E) Status line
         // ---------------------------------------
-        cars.retain(|car| {
-            let distance_to_dest =
-                Vec2::new(car.car_rect.x, car.car_rect.y).distance(car.dest_point);
-            // println!(
-            //     "Car ID: {}, Car Rect: {:?}, Dest Point: {:?}, Distance to Dest: {}",
-            //     car.id, car.car_rect, car.dest_point, distance_to_dest
-            // );
-            if distance_to_dest < 20.0
-                || car.car_rect.x < -50.0
-                || car.car_rect.x > WINDOW_SIZE as f32 + 60.0
-                || car.car_rect.y < -50.0
-                || car.car_rect.y > WINDOW_SIZE as f32 + 60.0
-            {
-                update_intersection_time(car.lifetime.elapsed().as_secs_f32());
-                increment_vehicle_count();
-                false
+        let state = if !collided.is_empty() {
+            "paused on collision"
+        } else if paused {
+            "paused"
+        } else {
+            "running"
+        };
+        let status = format!(
+            "Tick {} ({:.1}s)   {}x   {}   {} cars   {} queued   random spawns {}",
+            simulation.tick(),
+            simulation.tick() as f32 * TICK_DURATION,
+            time_scale,
+            state,
+            simulation.cars().len(),
+            simulation.entry_queue_total(),
+            if simulation.is_random_generation() {
+                "on"
             } else {
-                true
-            }
-        });
-
-        // Print the count of cars
-        // println!("Number of cars: {}", cars.len());
+                "off"
+            },
+        );
+        draw_text(
+            &texture_creator,
+            "assets/Roboto-Regular.ttf",
+            16,
+            &status,
+            Color::RGB(255, 255, 255),
+            10,
+            10,
+            canvas,
+            ttf_context,
+        )
+        .unwrap();
 
         // Present the frame and wait
         canvas.present();
@@ -301,15 +373,67 @@ fn render_simulation(
     }
 }
 
+/// Draw a signal head at the stop line of every lane, next to its arrow,
+/// when the policy drives traffic lights.
+fn draw_signal_heads(
+    canvas: &mut sdl2::render::Canvas<sdl2::video::Window>,
+    simulation: &Simulation,
+) {
+    let line_spacing = simulation.scenario().layout.line_spacing();
+    let size = line_spacing / 3;
+    for route in Route::ALL {
+        let Some(signal) = simulation.signal(route) else {
+            continue;
+        };
+        canvas.set_draw_color(match signal {
+            SignalColor::Green => Color::RGB(0, 200, 0),
+            SignalColor::Yellow => Color::RGB(255, 200, 0),
+            SignalColor::Red => Color::RGB(220, 0, 0),
+        });
+
+        // Centre of the lane, just behind the lane arrow painted in the
+        // cell before the stop line
+        let lane_center = route.lane() * line_spacing + line_spacing / 2;
+        let (x, y) = match route.approach() {
+            Approach::Right => (11 * line_spacing + 2, lane_center - size / 2),
+            Approach::Down => (lane_center - size / 2, 11 * line_spacing + 2),
+            Approach::Left => (3 * line_spacing - size - 2, lane_center - size / 2),
+            Approach::Up => (lane_center - size / 2, 3 * line_spacing - size - 2),
+        };
+        let head = sdl2::rect::Rect::new(x, y, size as u32, size as u32);
+        canvas.fill_rect(head).unwrap();
+    }
+}
+
+/// Outline a car involved in a collision in red.
+fn draw_collision_highlight(canvas: &mut sdl2::render::Canvas<sdl2::video::Window>, car: &Car) {
+    canvas.set_draw_color(Color::RGB(255, 0, 0));
+    for outset in 0..3 {
+        let grown = OrientedRect::new(
+            car.body.center,
+            Vec2::new(
+                car.body.size.x + 2.0 * outset as f32,
+                car.body.size.y + 2.0 * outset as f32,
+            ),
+            car.body.angle,
+        );
+        canvas.draw_lines(outline(&grown).as_slice()).unwrap();
+    }
+}
+
 fn render_metrics(
-    mut canvas: &mut sdl2::render::Canvas<sdl2::video::Window>,
+    canvas: &mut sdl2::render::Canvas<sdl2::video::Window>,
     event_pump: &mut sdl2::EventPump,
     ttf_context: &sdl2::ttf::Sdl2TtfContext,
+    simulation: &Simulation,
 ) {
+    let window_size = simulation.scenario().layout.window_size as i32;
     canvas.set_draw_color(Color::RGB(0, 0, 0));
     canvas.clear();
 
-    let (title, stats) = get_metrics_display();
+    let (title, stats) = simulation
+        .metrics()
+        .display(simulation.seed(), simulation.policy_name());
 
     // Draw title
     draw_text(
@@ -318,25 +442,26 @@ fn render_metrics(
         48,
         &title,
         Color::RGB(255, 255, 255),
-        WINDOW_SIZE as i32 / 2 - 200,
+        window_size / 2 - 200,
         100,
-        &mut canvas,
-        &ttf_context,
+        canvas,
+        ttf_context,
     )
     .unwrap();
 
-    // Draw stats
+    // Draw stats, squeezing the lines together if they don't fit
+    let line_height = ((window_size - 170) / stats.len().max(1) as i32).min(50);
     for (i, stat) in stats.iter().enumerate() {
         draw_text(
             &canvas.texture_creator(),
             "assets/Roboto-Regular.ttf",
-            32,
+            (line_height * 2 / 3) as u16,
             stat,
             Color::RGB(255, 255, 255),
-            WINDOW_SIZE as i32 / 2 - 200,
-            150 + (i as i32 * 50),
-            &mut canvas,
-            &ttf_context,
+            window_size / 2 - 200,
+            150 + (i as i32 * line_height),
+            canvas,
+            ttf_context,
         )
         .unwrap();
     }
@@ -360,13 +485,16 @@ fn render_metrics(
 }
 
 // Function to draw the lines once
-fn draw_lines(canvas: &mut sdl2::render::Canvas<sdl2::video::Window>) {
+fn draw_lines(canvas: &mut sdl2::render::Canvas<sdl2::video::Window>, layout: &Layout) {
+    let line_spacing = layout.line_spacing();
+    let window_size = layout.window_size as i32;
+
     // the x point to stop at (before intersection)
-    let before_intersection: i32 = 4 * LINE_SPACING; // eman approved nadeer is Supercalifragilisticexpialidocious
-    let after_intersection: i32 = 10 * LINE_SPACING;
+    let before_intersection: i32 = 4 * line_spacing; // eman approved nadeer is Supercalifragilisticexpialidocious
+    let after_intersection: i32 = 10 * line_spacing;
 
     for line in 4..=10 {
-        let x = line * LINE_SPACING;
+        let x = line * line_spacing;
         if line == 4 || line == 10 || line == 7 {
             canvas.set_draw_color(Color::RGB(255, 255, 255)); // White color for the first, middle, and last lines
         } else {
@@ -375,25 +503,32 @@ fn draw_lines(canvas: &mut sdl2::render::Canvas<sdl2::video::Window>) {
 
         if line == 7 {
             // vertical line
-            canvas.draw_line((x, 0), (x, WINDOW_SIZE as i32)).unwrap();
+            canvas.draw_line((x, 0), (x, window_size)).unwrap();
             // horizontal line
-            canvas.draw_line((0, x), (WINDOW_SIZE as i32, x)).unwrap();
+            canvas.draw_line((0, x), (window_size, x)).unwrap();
         } else {
             // vertical line before intersection
             canvas.draw_line((x, 0), (x, before_intersection)).unwrap();
             // vertical line after intersection
             canvas
-                .draw_line((x, after_intersection), (x, WINDOW_SIZE as i32))
+                .draw_line((x, after_intersection), (x, window_size))
                 .unwrap();
 
             // horizontal line before intersection
             canvas.draw_line((0, x), (before_intersection, x)).unwrap();
             // horizontal line after intersection
             canvas
-                .draw_line((after_intersection, x), (WINDOW_SIZE as i32, x))
+                .draw_line((after_intersection, x), (window_size, x))
                 .unwrap();
         }
-        draw_arrows(canvas, line, x, before_intersection, after_intersection);
+        draw_arrows(
+            canvas,
+            line,
+            x,
+            line_spacing,
+            before_intersection,
+            after_intersection,
+        );
     }
 }
 
@@ -401,6 +536,7 @@ fn draw_arrows(
     canvas: &mut sdl2::render::Canvas<sdl2::video::Window>,
     line: i32,
     x: i32,
+    line_spacing: i32,
     before_intersection: i32,
     after_intersection: i32,
 ) {
@@ -409,9 +545,9 @@ fn draw_arrows(
             draw_image(
                 canvas,
                 x,
-                before_intersection - LINE_SPACING,
-                LINE_SPACING as u32,
-                LINE_SPACING as u32,
+                before_intersection - line_spacing,
+                line_spacing as u32,
+                line_spacing as u32,
                 "assets/arrow.turn.png",
                 180.0,
             );
@@ -419,8 +555,8 @@ fn draw_arrows(
                 canvas,
                 after_intersection,
                 x,
-                LINE_SPACING as u32,
-                LINE_SPACING as u32,
+                line_spacing as u32,
+                line_spacing as u32,
                 "assets/arrow.turn.png",
                 270.0,
             );
@@ -429,9 +565,9 @@ fn draw_arrows(
             draw_image(
                 canvas,
                 x,
-                before_intersection - LINE_SPACING,
-                LINE_SPACING as u32,
-                LINE_SPACING as u32,
+                before_intersection - line_spacing,
+                line_spacing as u32,
+                line_spacing as u32,
                 "assets/arrow.up.png",
                 180.0,
             );
@@ -439,8 +575,8 @@ fn draw_arrows(
                 canvas,
                 after_intersection,
                 x,
-                LINE_SPACING as u32,
-                LINE_SPACING as u32,
+                line_spacing as u32,
+                line_spacing as u32,
                 "assets/arrow.up.png",
                 270.0,
             );
@@ -449,9 +585,9 @@ fn draw_arrows(
             draw_image(
                 canvas,
                 x,
-                before_intersection - LINE_SPACING,
-                LINE_SPACING as u32,
-                LINE_SPACING as u32,
+                before_intersection - line_spacing,
+                line_spacing as u32,
+                line_spacing as u32,
                 "assets/arrow.turn.left.png",
                 180.0,
             );
@@ -459,8 +595,8 @@ fn draw_arrows(
                 canvas,
                 after_intersection,
                 x,
-                LINE_SPACING as u32,
-                LINE_SPACING as u32,
+                line_spacing as u32,
+                line_spacing as u32,
                 "assets/arrow.turn.left.png",
                 270.0,
             );
@@ -468,10 +604,10 @@ fn draw_arrows(
         7 => {
             draw_image(
                 canvas,
-                before_intersection - LINE_SPACING,
+                before_intersection - line_spacing,
                 x,
-                LINE_SPACING as u32,
-                LINE_SPACING as u32,
+                line_spacing as u32,
+                line_spacing as u32,
                 "assets/arrow.turn.left.png",
                 90.0,
             );
@@ -479,8 +615,8 @@ fn draw_arrows(
                 canvas,
                 x,
                 after_intersection,
-                LINE_SPACING as u32,
-                LINE_SPACING as u32,
+                line_spacing as u32,
+                line_spacing as u32,
                 "assets/arrow.turn.left.png",
                 0.0,
             );
@@ -488,10 +624,10 @@ fn draw_arrows(
         8 => {
             draw_image(
                 canvas,
-                before_intersection - LINE_SPACING,
+                before_intersection - line_spacing,
                 x,
-                LINE_SPACING as u32,
-                LINE_SPACING as u32,
+                line_spacing as u32,
+                line_spacing as u32,
                 "assets/arrow.up.png",
                 90.0,
             );
@@ -499,8 +635,8 @@ fn draw_arrows(
                 canvas,
                 x,
                 after_intersection,
-                LINE_SPACING as u32,
-                LINE_SPACING as u32,
+                line_spacing as u32,
+                line_spacing as u32,
                 "assets/arrow.up.png",
                 0.0,
             );
@@ -508,10 +644,10 @@ fn draw_arrows(
         9 => {
             draw_image(
                 canvas,
-                before_intersection - LINE_SPACING,
+                before_intersection - line_spacing,
                 x,
-                LINE_SPACING as u32,
-                LINE_SPACING as u32,
+                line_spacing as u32,
+                line_spacing as u32,
                 "assets/arrow.turn.png",
                 90.0,
             );
@@ -519,8 +655,8 @@ fn draw_arrows(
                 canvas,
                 x,
                 after_intersection,
-                LINE_SPACING as u32,
-                LINE_SPACING as u32,
+                line_spacing as u32,
+                line_spacing as u32,
                 "assets/arrow.turn.png",
                 0.0,
             );
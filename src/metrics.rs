@@ -1,117 +1,308 @@
-use lazy_static::lazy_static;
-use std::sync::Mutex;
+use crate::constants::TICK_DURATION;
+use crate::event::{SimEvent, Subscriber};
+use crate::route::Route;
+use crate::stats::{histogram, Summary};
+use crate::trip::TripRecord;
+use serde::Serialize;
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Metrics {
-    pub vehicle_count: u32,
     pub cars_spawned: u32, // New field to track total spawns
+    // Cars that showed up at the edge of the map, spawned or not
+    pub cars_arrived: u32,
+    // Cars that had to wait in an entry queue before spawning
+    pub spawns_delayed: u32,
+    // Cars turned away by a full entry queue
+    pub spawns_rejected: u32,
+    // Seconds every spawned car spent in its entry queue
+    pub entry_delays: Vec<f32>,
     pub min_vehicle_speed: f32,
     pub max_vehicle_speed: f32,
-    pub min_intersection_pass_time: f32,
-    pub max_intersection_pass_time: f32,
     pub close_call_count: u32,
+    pub collision_count: u32,
+    // Comfort of every car that completed its journey
+    pub peak_decelerations: Vec<f32>,
+    pub mean_abs_jerks: Vec<f32>,
+    pub hard_braking_events: Vec<u32>,
+    // One record per car that completed its journey
+    pub trips: Vec<TripRecord>,
+    // State of the road at the end of every tick
+    pub ticks: Vec<TickSample>,
 }
 
-lazy_static! {
-    static ref METRICS: Mutex<Metrics> = Mutex::new(Metrics {
-        vehicle_count: 0,
-        cars_spawned: 0,
-        min_vehicle_speed: f32::MAX,
-        max_vehicle_speed: 0.0,  // Changed from MIN to track actual speeds
-        min_intersection_pass_time: f32::MAX,
-        max_intersection_pass_time: 0.0,  // Changed from MIN to track actual times
-        close_call_count: 0,
-    });
+/// Snapshot of the road taken at the end of a tick.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct TickSample {
+    pub tick: u64,
+    /// Simulated seconds since the start of the run.
+    pub time: f32,
+    /// Cars on the map.
+    pub cars: u32,
+    /// Cars waiting before the intersection, per approach.
+    pub queue_right: u32,
+    pub queue_down: u32,
+    pub queue_left: u32,
+    pub queue_up: u32,
+    /// Cars waiting off the map to enter, per approach.
+    pub entry_right: u32,
+    pub entry_down: u32,
+    pub entry_left: u32,
+    pub entry_up: u32,
+    /// Cars that left the map during this tick.
+    pub completed: u32,
+    /// Cars that left the map since the start of the run.
+    pub completed_total: u32,
+    /// Slowest and fastest car on the map, in px/s.
+    pub min_speed: Option<f32>,
+    pub max_speed: Option<f32>,
 }
 
-// Public functions for metrics updates
-pub fn increment_vehicle_count() {
-    let mut metrics = METRICS.lock().unwrap();
-    metrics.vehicle_count += 1;
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            cars_spawned: 0,
+            cars_arrived: 0,
+            spawns_delayed: 0,
+            spawns_rejected: 0,
+            entry_delays: Vec::new(),
+            min_vehicle_speed: f32::MAX,
+            max_vehicle_speed: 0.0, // Changed from MIN to track actual speeds
+            close_call_count: 0,
+            collision_count: 0,
+            peak_decelerations: Vec::new(),
+            mean_abs_jerks: Vec::new(),
+            hard_braking_events: Vec::new(),
+            trips: Vec::new(),
+            ticks: Vec::new(),
+        }
+    }
 }
 
-pub fn increment_close_call_count() {
-    let mut metrics = METRICS.lock().unwrap();
-    metrics.close_call_count += 1;
+/// Gathers the metrics of one simulation from its events. The simulation
+/// owns it, so every run starts from zero.
+#[derive(Default)]
+pub struct MetricsCollector {
+    metrics: Metrics,
 }
 
-pub fn update_intersection_time(time: f32) {
-    let mut metrics = METRICS.lock().unwrap();
-    if time < metrics.min_intersection_pass_time {
-        metrics.min_intersection_pass_time = time;
-    }
-    if time > metrics.max_intersection_pass_time {
-        metrics.max_intersection_pass_time = time;
+impl MetricsCollector {
+    /// Everything gathered so far.
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
     }
 }
 
-pub fn update_vehicle_speed(speed: f32) {
-    let mut metrics = METRICS.lock().unwrap();
-    if speed < metrics.min_vehicle_speed {
-        metrics.min_vehicle_speed = speed;
-    }
-    if speed > metrics.max_vehicle_speed {
-        metrics.max_vehicle_speed = speed;
+impl Subscriber for MetricsCollector {
+    fn on_event(&mut self, event: &SimEvent) {
+        let metrics = &mut self.metrics;
+        match event {
+            SimEvent::Arrived { .. } => metrics.cars_arrived += 1,
+            SimEvent::Spawned {
+                tick, arrival_tick, ..
+            } => {
+                metrics.cars_spawned += 1;
+                if tick > arrival_tick {
+                    metrics.spawns_delayed += 1;
+                }
+                let delay = (tick - arrival_tick) as f32 * TICK_DURATION;
+                metrics.entry_delays.push(delay);
+            }
+            SimEvent::TurnedAway { .. } => metrics.spawns_rejected += 1,
+            SimEvent::CloseCall(_) => metrics.close_call_count += 1,
+            SimEvent::Collision(_) => metrics.collision_count += 1,
+            SimEvent::Despawned { trip, comfort } => {
+                metrics.trips.push(trip.clone());
+                metrics.peak_decelerations.push(comfort.peak_deceleration);
+                metrics.mean_abs_jerks.push(comfort.mean_abs_jerk());
+                metrics
+                    .hard_braking_events
+                    .push(comfort.hard_braking_events);
+            }
+            SimEvent::TickEnded(sample) => {
+                if let Some(speed) = sample.min_speed {
+                    metrics.min_vehicle_speed = metrics.min_vehicle_speed.min(speed);
+                }
+                if let Some(speed) = sample.max_speed {
+                    metrics.max_vehicle_speed = metrics.max_vehicle_speed.max(speed);
+                }
+                metrics.ticks.push(*sample);
+            }
+            _ => {}
+        }
     }
 }
 
-pub fn increment_spawn_count() {
-    let mut metrics = METRICS.lock().unwrap();
-    metrics.cars_spawned += 1;
-}
+/// Bars in the trip time histogram of `Metrics::trip_report`.
+const HISTOGRAM_BINS: usize = 8;
 
-// Remove unused get_metrics function
-// pub fn get_metrics() -> Metrics {
-//     *METRICS.lock().unwrap()
-// }
+impl Metrics {
+    /// Cars still waiting in the entry queues when the run stopped.
+    pub fn entry_queue_at_end(&self) -> u32 {
+        self.ticks.last().map_or(0, |sample| {
+            sample.entry_right + sample.entry_down + sample.entry_left + sample.entry_up
+        })
+    }
 
-pub fn get_metrics_display() -> (String, Vec<String>) {
-    let metrics = METRICS.lock().unwrap();
+    /// Title and lines of the summary shown when the simulation stops.
+    pub fn display(&self, seed: u64, policy: &str) -> (String, Vec<String>) {
+        let title = if self.cars_spawned == 0 {
+            "No Cars Were Spawned".to_string()
+        } else {
+            "Simulation Stopped".to_string()
+        };
 
-    let title = if metrics.cars_spawned == 0 {
-        "No Cars Were Spawned".to_string()
-    } else {
-        "Simulation Stopped".to_string()
-    };
+        let mut stats = Vec::new();
+        stats.push(format!("Seed: {}", seed));
+        stats.push(format!("Policy: {}", policy));
+        stats.push(format!("Total Cars Spawned: {}", self.cars_spawned));
+        stats.push(format!(
+            "Arrivals: {}, {} delayed, {} turned away, {} still queued",
+            self.cars_arrived,
+            self.spawns_delayed,
+            self.spawns_rejected,
+            self.entry_queue_at_end(),
+        ));
+        if self.spawns_delayed > 0 {
+            if let Some(delay) = Summary::of(&self.entry_delays) {
+                stats.push(format!(
+                    "Entry Delay: mean {:.2}s, p95 {:.2}s, max {:.2}s",
+                    delay.mean, delay.p95, delay.max
+                ));
+            }
+        }
+        stats.push(format!("Cars Completed Journey: {}", self.trips.len()));
+        stats.push(format!("Collisions: {}", self.collision_count));
 
-    let mut stats = Vec::new();
-    stats.push(format!("Total Cars Spawned: {}", metrics.cars_spawned));
-    stats.push(format!("Cars Completed Journey: {}", metrics.vehicle_count));
-    stats.push("Collisions: 0".to_string());
+        // Speed metrics
+        if self.cars_spawned > 0 {
+            if self.max_vehicle_speed > 0.0 {
+                stats.push(format!(
+                    "Max Vehicle Velocity: {:.2} px/s",
+                    self.max_vehicle_speed
+                ));
+                stats.push(format!(
+                    "Min Vehicle Velocity: {:.2} px/s",
+                    self.min_vehicle_speed
+                ));
+            } else {
+                stats.push("Max Vehicle Velocity: None".to_string());
+                stats.push("Min Vehicle Velocity: None".to_string());
+            }
+        }
 
-    // Speed metrics
-    if metrics.cars_spawned > 0 {
-        if metrics.max_vehicle_speed > 0.0 {
+        // Time metrics
+        let trip_times: Vec<f32> = self.trips.iter().map(TripRecord::trip_time).collect();
+        match Summary::of(&trip_times) {
+            Some(trip) => stats.push(format!(
+                "Trip Time: mean {:.2}s, median {:.2}s, p95 {:.2}s",
+                trip.mean, trip.median, trip.p95
+            )),
+            None => stats.push("Trip Time: No completions".to_string()),
+        }
+        let wait_times: Vec<f32> = self.trips.iter().map(|trip| trip.wait_time).collect();
+        if let Some(wait) = Summary::of(&wait_times) {
             stats.push(format!(
-                "Max Vehicle Velocity: {:.2}",
-                metrics.max_vehicle_speed
+                "Wait Time: mean {:.2}s, median {:.2}s, p95 {:.2}s",
+                wait.mean, wait.median, wait.p95
             ));
+        }
+        let average_speeds: Vec<f32> = self.trips.iter().map(|trip| trip.average_speed).collect();
+        if let Some(speed) = Summary::of(&average_speeds) {
             stats.push(format!(
-                "Min Vehicle Velocity: {:.2}",
-                metrics.min_vehicle_speed
+                "Average Speed: mean {:.0}, median {:.0}, p95 {:.0} px/s",
+                speed.mean, speed.median, speed.p95
             ));
-        } else {
-            stats.push("Max Vehicle Velocity: None".to_string());
-            stats.push("Min Vehicle Velocity: None".to_string());
         }
-    }
 
-    // Time metrics
-    if metrics.vehicle_count > 0 {
-        stats.push(format!(
-            "Max Time to Pass: {:.2}s",
-            metrics.max_intersection_pass_time
-        ));
-        stats.push(format!(
-            "Min Time to Pass: {:.2}s",
-            metrics.min_intersection_pass_time
-        ));
-    } else {
-        stats.push("Max Time to Pass: No completions".to_string());
-        stats.push("Min Time to Pass: No completions".to_string());
+        stats.push(format!("Close Calls: {}", self.close_call_count));
+
+        // Comfort metrics
+        match Summary::of(&self.peak_decelerations) {
+            Some(braking) => stats.push(format!(
+                "Peak Braking: mean {:.0}, p95 {:.0}, max {:.0} px/s²",
+                braking.mean, braking.p95, braking.max
+            )),
+            None => stats.push("Peak Braking: No completions".to_string()),
+        }
+        if let Some(jerk) = Summary::of(&self.mean_abs_jerks) {
+            stats.push(format!(
+                "Mean |Jerk|: mean {:.0}, p95 {:.0}, max {:.0} px/s³",
+                jerk.mean, jerk.p95, jerk.max
+            ));
+        }
+        if !self.hard_braking_events.is_empty() {
+            let events: u32 = self.hard_braking_events.iter().sum();
+            let cars = self
+                .hard_braking_events
+                .iter()
+                .filter(|&&count| count > 0)
+                .count();
+            stats.push(format!(
+                "Hard Braking: {} events in {:.0}% of cars",
+                events,
+                100.0 * cars as f32 / self.hard_braking_events.len() as f32
+            ));
+        }
+
+        (title, stats)
     }
 
-    stats.push(format!("Close Calls: {}", (metrics.close_call_count/60) as i32));
+    /// Longer report on the completed trips, for the terminal: trip, wait and
+    /// crossing times per route, and a histogram of all trip times.
+    pub fn trip_report(&self) -> Vec<String> {
+        let mut report = Vec::new();
+        if self.trips.is_empty() {
+            report.push("No trips completed".to_string());
+            return report;
+        }
 
-    (title, stats)
+        report.push(format!(
+            "{:<6} {:>5} {:>22} {:>22} {:>10}",
+            "Route", "Cars", "Trip mean/med/p95 (s)", "Wait mean/med/p95 (s)", "Cross (s)"
+        ));
+        for route in Route::ALL {
+            let trips: Vec<&TripRecord> = self
+                .trips
+                .iter()
+                .filter(|trip| trip.route == route)
+                .collect();
+            let trip_times: Vec<f32> = trips.iter().map(|trip| trip.trip_time()).collect();
+            let wait_times: Vec<f32> = trips.iter().map(|trip| trip.wait_time).collect();
+            let crossing_times: Vec<f32> = trips
+                .iter()
+                .filter_map(|trip| trip.crossing_time())
+                .collect();
+            let (Some(trip), Some(wait)) = (Summary::of(&trip_times), Summary::of(&wait_times))
+            else {
+                report.push(format!("{:<6} {:>5}", route.to_string(), 0));
+                continue;
+            };
+            let crossing = Summary::of(&crossing_times)
+                .map_or("-".to_string(), |crossing| format!("{:.2}", crossing.mean));
+            report.push(format!(
+                "{:<6} {:>5} {:>22} {:>22} {:>10}",
+                route.to_string(),
+                trips.len(),
+                format!("{:.2}/{:.2}/{:.2}", trip.mean, trip.median, trip.p95),
+                format!("{:.2}/{:.2}/{:.2}", wait.mean, wait.median, wait.p95),
+                crossing
+            ));
+        }
+
+        report.push(String::new());
+        report.push("Trip time histogram:".to_string());
+        let trip_times: Vec<f32> = self.trips.iter().map(TripRecord::trip_time).collect();
+        let bins = histogram(&trip_times, HISTOGRAM_BINS);
+        let most = bins.iter().map(|bin| bin.count).max().unwrap_or(1).max(1);
+        for bin in bins {
+            report.push(format!(
+                "{:>6.2}-{:<6.2}s {:>5} {}",
+                bin.start,
+                bin.end,
+                bin.count,
+                "#".repeat(bin.count * 40 / most)
+            ));
+        }
+        report
+    }
 }
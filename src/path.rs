@@ -0,0 +1,238 @@
+use crate::geometry::Vec2;
+use crate::route::{Approach, Direction, Route};
+use crate::scenario::Layout;
+use std::f32::consts::{FRAC_PI_2, PI};
+
+/// Where a car is on its path and which way it faces.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pose {
+    /// Center of the car.
+    pub position: Vec2,
+    /// Heading in radians, in screen coordinates: 0 is East, PI/2 is South.
+    pub heading: f32,
+}
+
+impl Pose {
+    /// Compass heading closest to `heading`.
+    pub fn direction(&self) -> Direction {
+        let (dx, dy) = (self.heading.cos(), self.heading.sin());
+        if dx.abs() >= dy.abs() {
+            if dx > 0.0 {
+                Direction::East
+            } else {
+                Direction::West
+            }
+        } else if dy > 0.0 {
+            Direction::South
+        } else {
+            Direction::North
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Line {
+        from: Vec2,
+        to: Vec2,
+    },
+    /// Quarter circle around `center`, starting at `start_angle` and turning
+    /// by `sweep` radians (positive is clockwise on screen).
+    Arc {
+        center: Vec2,
+        radius: f32,
+        start_angle: f32,
+        sweep: f32,
+    },
+}
+
+impl Segment {
+    fn length(&self) -> f32 {
+        match self {
+            Segment::Line { from, to } => from.distance(*to),
+            Segment::Arc { radius, sweep, .. } => radius * sweep.abs(),
+        }
+    }
+
+    fn pose_at(&self, distance: f32) -> Pose {
+        match self {
+            Segment::Line { from, to } => {
+                let length = from.distance(*to);
+                let t = if length > 0.0 { distance / length } else { 0.0 };
+                Pose {
+                    position: Vec2::new(from.x + (to.x - from.x) * t, from.y + (to.y - from.y) * t),
+                    heading: (to.y - from.y).atan2(to.x - from.x),
+                }
+            }
+            Segment::Arc {
+                center,
+                radius,
+                start_angle,
+                sweep,
+            } => {
+                let angle = start_angle + sweep.signum() * distance / radius;
+                Pose {
+                    position: Vec2::new(
+                        center.x + radius * angle.cos(),
+                        center.y + radius * angle.sin(),
+                    ),
+                    heading: angle + sweep.signum() * FRAC_PI_2,
+                }
+            }
+        }
+    }
+}
+
+/// Centerline a car follows from its spawn point to just past the window edge:
+/// straight down the entry lane, a circular arc through the intersection when
+/// the route turns, then straight down the exit lane.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path {
+    segments: Vec<Segment>,
+}
+
+impl Path {
+    /// Path of a car `car_length` pixels long driving `route` on `layout`.
+    pub fn for_route(route: Route, layout: &Layout, car_length: f32) -> Self {
+        let line_spacing = layout.line_spacing();
+        let entry_direction = route.initial_direction();
+        let exit_direction = route.final_direction();
+        let entry_lane = lane_center(route.lane(), line_spacing);
+        let exit_lane = lane_center(route.exit_lane(), line_spacing);
+
+        let offscreen = |side| offscreen(side, layout.window_size, car_length);
+        let start = along_lane(entry_direction, entry_lane, offscreen(route.approach()));
+        let end = along_lane(exit_direction, exit_lane, offscreen(route.exit()));
+
+        if entry_direction == exit_direction {
+            return Self {
+                segments: vec![Segment::Line {
+                    from: start,
+                    to: end,
+                }],
+            };
+        }
+
+        // The lanes cross at `corner`; round it off with an arc that starts
+        // right at the edge of the core intersection.
+        let corner = along_lane(entry_direction, entry_lane, exit_lane);
+        let entry_edge = along_lane(
+            entry_direction,
+            entry_lane,
+            core_edge(route.approach(), line_spacing),
+        );
+        let radius = entry_edge.distance(corner);
+
+        let entry_unit = unit(entry_direction);
+        let exit_unit = unit(exit_direction);
+        let arc_start = Vec2::new(
+            corner.x - entry_unit.x * radius,
+            corner.y - entry_unit.y * radius,
+        );
+        let arc_end = Vec2::new(
+            corner.x + exit_unit.x * radius,
+            corner.y + exit_unit.y * radius,
+        );
+        let center = Vec2::new(
+            arc_start.x + exit_unit.x * radius,
+            arc_start.y + exit_unit.y * radius,
+        );
+
+        let start_angle = (arc_start.y - center.y).atan2(arc_start.x - center.x);
+        let end_angle = (arc_end.y - center.y).atan2(arc_end.x - center.x);
+        let mut sweep = end_angle - start_angle;
+        if sweep > PI {
+            sweep -= 2.0 * PI;
+        } else if sweep < -PI {
+            sweep += 2.0 * PI;
+        }
+
+        Self {
+            segments: vec![
+                Segment::Line {
+                    from: start,
+                    to: arc_start,
+                },
+                Segment::Arc {
+                    center,
+                    radius,
+                    start_angle,
+                    sweep,
+                },
+                Segment::Line {
+                    from: arc_end,
+                    to: end,
+                },
+            ],
+        }
+    }
+
+    pub fn length(&self) -> f32 {
+        self.segments.iter().map(Segment::length).sum()
+    }
+
+    /// Pose of a car that has driven `distance` pixels along the path.
+    /// Distances past either end are clamped to it.
+    pub fn pose_at(&self, distance: f32) -> Pose {
+        let mut remaining = distance.max(0.0);
+        for segment in &self.segments {
+            let length = segment.length();
+            if remaining <= length {
+                return segment.pose_at(remaining);
+            }
+            remaining -= length;
+        }
+        let last = self.segments.last().expect("Path has no segments");
+        last.pose_at(last.length())
+    }
+
+    /// Distance along the path at which the turn is complete, or 0 for
+    /// straight paths.
+    pub fn turn_end(&self) -> f32 {
+        match self.segments.as_slice() {
+            [entry, arc, _] => entry.length() + arc.length(),
+            _ => 0.0,
+        }
+    }
+}
+
+/// Unit vector pointing in `direction` on screen.
+fn unit(direction: Direction) -> Vec2 {
+    match direction {
+        Direction::North => Vec2::new(0.0, -1.0),
+        Direction::South => Vec2::new(0.0, 1.0),
+        Direction::East => Vec2::new(1.0, 0.0),
+        Direction::West => Vec2::new(-1.0, 0.0),
+    }
+}
+
+fn lane_center(lane: i32, line_spacing: i32) -> f32 {
+    (lane * line_spacing) as f32 + line_spacing as f32 / 2.0
+}
+
+/// Point in a lane running in `direction`: `lane` is its cross-axis
+/// coordinate and `along` the coordinate along it.
+fn along_lane(direction: Direction, lane: f32, along: f32) -> Vec2 {
+    if direction.is_horizontal() {
+        Vec2::new(along, lane)
+    } else {
+        Vec2::new(lane, along)
+    }
+}
+
+/// Coordinate along a lane just past the `side` edge of the window,
+/// where a whole car `car_length` pixels long is off screen.
+fn offscreen(side: Approach, window_size: u32, car_length: f32) -> f32 {
+    match side {
+        Approach::Right | Approach::Down => window_size as f32 + car_length,
+        Approach::Left | Approach::Up => -car_length,
+    }
+}
+
+/// Coordinate of the core intersection edge that cars from `approach` cross first.
+fn core_edge(approach: Approach, line_spacing: i32) -> f32 {
+    match approach {
+        Approach::Right | Approach::Down => (10 * line_spacing) as f32,
+        Approach::Left | Approach::Up => (4 * line_spacing) as f32,
+    }
+}
@@ -0,0 +1,125 @@
+use crate::car::Car;
+use crate::geometry::{FRect, OrientedRect};
+use crate::reservation::ReservationPolicy;
+use crate::route::{Route, Turn};
+use crate::scenario::Scenario;
+use crate::signal::{SignalColor, SignalPolicy};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// Decides which cars waiting at the edge of the core intersection may enter.
+///
+/// The simulation consults its policy every tick for each car that has not
+/// entered the core intersection yet and is within its `lookahead` of the stop
+/// line. Cars that are refused brake towards the stop line and ask again on
+/// the next tick.
+pub trait IntersectionPolicy {
+    /// Name shown in the metrics summary.
+    fn name(&self) -> &'static str;
+
+    /// How far before the stop line `car` starts asking to enter, in pixels.
+    fn lookahead(&self, car: &Car) -> f32 {
+        car.radar_size.long_edge
+    }
+
+    /// Whether admitted cars can never block each other inside the box.
+    /// The simulation skips its deadlock breaker for such policies.
+    fn prevents_deadlock(&self) -> bool {
+        false
+    }
+
+    /// Whether `car` was let through ahead of every crossing route, so it
+    /// drives on without braking for cars on other routes.
+    fn has_right_of_way(&self, _car: &Car) -> bool {
+        false
+    }
+
+    /// Called once at the start of every tick, before any `may_enter`.
+    fn begin_tick(&mut self, _tick: u64, _cars: &[Car]) {}
+
+    /// Whether `car` may enter `core_intersection` now.
+    /// `cars` is every car on the road, including `car` itself.
+    fn may_enter(&mut self, car: &Car, cars: &[Car], core_intersection: &FRect) -> bool;
+
+    /// Signal shown to cars on `route`, for policies that drive traffic lights.
+    fn signal(&self, _route: Route) -> Option<SignalColor> {
+        None
+    }
+}
+
+/// Policies selectable at startup with `--policy`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyKind {
+    /// Lets cars in unless a conflicting route is already inside the box
+    #[default]
+    Heuristic,
+    /// Grants time-space reservations on a tile grid over the box
+    Reservation,
+    /// Fixed-time traffic lights with protected left-turn phases
+    Signal,
+}
+
+impl PolicyKind {
+    /// Create the policy, set up for `scenario`.
+    pub fn build(self, scenario: &Scenario) -> Box<dyn IntersectionPolicy> {
+        match self {
+            PolicyKind::Heuristic => Box::new(HeuristicPolicy {
+                max_left_turners: scenario.max_left_turners,
+            }),
+            PolicyKind::Reservation => Box::new(ReservationPolicy::new(&scenario.layout)),
+            PolicyKind::Signal => Box::new(SignalPolicy::new(scenario.signal_plan.clone())),
+        }
+    }
+}
+
+/// The original hand-tuned admission rules: at most `max_left_turners` (3 by
+/// default) inside the box at once, and routes that cross each other wait for
+/// the box to clear.
+pub struct HeuristicPolicy {
+    pub max_left_turners: usize,
+}
+
+impl IntersectionPolicy for HeuristicPolicy {
+    fn name(&self) -> &'static str {
+        "heuristic"
+    }
+
+    fn may_enter(&mut self, car: &Car, cars: &[Car], core_intersection: &FRect) -> bool {
+        let mut temp_cars = cars.to_vec();
+        temp_cars.retain(|other| other.id != car.id);
+        let core = OrientedRect::from(*core_intersection);
+
+        // Count cars currently turning left in the intersection
+        let left_turning_cars = temp_cars
+            .iter()
+            .filter(|other| {
+                other.body.intersects(&core)
+                    && other.route.turn() == Turn::Left
+                    && other.current_speed > 0.0 // Only count cars that are actually moving
+            })
+            .count();
+
+        // If the box already holds as many left-turners as allowed, make this car wait
+        if car.route.turn() == Turn::Left && left_turning_cars >= self.max_left_turners {
+            return false;
+        }
+
+        // Original behavior for other cases
+        let must_wait = match car.route {
+            Route::LR | Route::UR | Route::DL => {
+                left_turning_cars + 1 >= self.max_left_turners
+                    || temp_cars.iter().any(|other| {
+                        matches!(other.route, Route::LR | Route::UR | Route::RL | Route::DL)
+                            && other.body.intersects(&core)
+                    })
+            }
+            Route::LU | Route::RD | Route::RL | Route::UD | Route::DU => temp_cars
+                .iter()
+                .any(|other| other.route == car.route && other.body.intersects(&core)),
+            // Right turns stay in the corner lane and never wait
+            Route::RU | Route::DR | Route::LD | Route::UL => false,
+        };
+        !must_wait
+    }
+}
@@ -0,0 +1,202 @@
+use crate::constants::TICK_DURATION;
+use crate::scenario::Scenario;
+use crate::simulation::{SimInput, Simulation};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// An input and the tick it was applied on, before that tick's step.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RecordedInput {
+    pub tick: u64,
+    pub input: SimInput,
+}
+
+/// Where a car was at the end of a tick. Rounded to keep recordings small.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CarSnapshot {
+    pub id: u32,
+    pub x: f32,
+    pub y: f32,
+    pub heading: f32,
+}
+
+/// Everything needed to play a run again: the seed and scenario it
+/// started from and every input, plus optionally where every car was on each
+/// tick so the run can be shown even if the simulation has changed since.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recording {
+    /// Version of the program that made the recording.
+    pub version: String,
+    pub seed: u64,
+    pub scenario: Scenario,
+    /// Number of ticks the run lasted.
+    pub ticks: u64,
+    pub inputs: Vec<RecordedInput>,
+    /// Cars on the road at the end of each tick, starting with tick 1.
+    /// Empty unless the run was recorded with snapshots.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub snapshots: Vec<Vec<CarSnapshot>>,
+}
+
+impl Recording {
+    /// Read a recording written by `save`, warning if it will be re-run
+    /// by a different version than the one that made it.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let file =
+            File::open(path).map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+        let recording: Self = serde_json::from_reader(GzDecoder::new(BufReader::new(file)))
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+
+        // Without snapshots the run is simulated again, which only matches
+        // the original if the simulation hasn't changed since
+        let version = env!("CARGO_PKG_VERSION");
+        if recording.version != version && !recording.has_snapshots() {
+            eprintln!(
+                "Warning: {} was recorded by version {}, this is {version}; \
+                 re-running it may not match the original run",
+                path.display(),
+                recording.version
+            );
+        }
+        Ok(recording)
+    }
+
+    /// Write the recording to `path` as gzipped JSON.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let file =
+            File::create(path).map_err(|e| format!("Failed to create {}: {e}", path.display()))?;
+        let mut encoder = GzEncoder::new(BufWriter::new(file), Compression::default());
+        serde_json::to_writer(&mut encoder, self)
+            .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+        encoder
+            .finish()
+            .and_then(|mut writer| writer.flush())
+            .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+    }
+
+    /// Step `simulation`, started from this recording's seed and scenario,
+    /// up to the end of `tick`, applying the recorded inputs on the way.
+    pub fn play(&self, simulation: &mut Simulation, tick: u64) {
+        // Inputs are recorded in tick order, so a cursor walks them once
+        let mut next = self
+            .inputs
+            .partition_point(|recorded| recorded.tick < simulation.tick());
+        while simulation.tick() < tick {
+            while let Some(recorded) = self
+                .inputs
+                .get(next)
+                .filter(|recorded| recorded.tick == simulation.tick())
+            {
+                simulation.apply(recorded.input);
+                next += 1;
+            }
+            simulation.step(TICK_DURATION);
+        }
+    }
+
+    /// Whether the recording holds car snapshots to show instead of re-running.
+    pub fn has_snapshots(&self) -> bool {
+        !self.snapshots.is_empty()
+    }
+}
+
+/// Builds a `Recording` while a run is going on.
+pub struct Recorder {
+    recording: Recording,
+    snapshots: bool,
+}
+
+impl Recorder {
+    /// Start recording a run of `seed` and `scenario`, with a snapshot of
+    /// every car on each tick if `snapshots` is set.
+    pub fn new(seed: u64, scenario: Scenario, snapshots: bool) -> Self {
+        Self {
+            recording: Recording {
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                seed,
+                scenario,
+                ticks: 0,
+                inputs: Vec::new(),
+                snapshots: Vec::new(),
+            },
+            snapshots,
+        }
+    }
+
+    /// Note `input`, about to be applied to `simulation`.
+    pub fn input(&mut self, simulation: &Simulation, input: SimInput) {
+        self.recording.inputs.push(RecordedInput {
+            tick: simulation.tick(),
+            input,
+        });
+    }
+
+    /// Note the end of a tick of `simulation`.
+    pub fn tick(&mut self, simulation: &Simulation) {
+        self.recording.ticks = simulation.tick();
+        if self.snapshots {
+            let round = |value: f32, scale: f32| (value * scale).round() / scale;
+            self.recording.snapshots.push(
+                simulation
+                    .cars()
+                    .iter()
+                    .map(|car| CarSnapshot {
+                        id: car.id,
+                        x: round(car.body.center.x, 10.0),
+                        y: round(car.body.center.y, 10.0),
+                        heading: round(car.heading, 1000.0),
+                    })
+                    .collect(),
+            );
+        }
+    }
+
+    pub fn finish(self) -> Recording {
+        self.recording
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::route::Approach;
+
+    #[test]
+    fn playback_applies_inputs_on_their_ticks() {
+        let inputs = [
+            (0, SimInput::SpawnFrom(Approach::Right)),
+            (0, SimInput::SpawnFrom(Approach::Up)),
+            (90, SimInput::ToggleRandomGeneration),
+            (250, SimInput::SpawnFrom(Approach::Left)),
+            (250, SimInput::SpawnFrom(Approach::Down)),
+            (600, SimInput::ToggleRandomGeneration),
+        ];
+        let mut original = Simulation::new(3, Scenario::default());
+        let mut recorder = Recorder::new(3, Scenario::default(), false);
+        for tick in 0..900 {
+            for &(_, input) in inputs.iter().filter(|(at, _)| *at == tick) {
+                recorder.input(&original, input);
+                original.apply(input);
+            }
+            original.step(TICK_DURATION);
+            recorder.tick(&original);
+        }
+        let recording = recorder.finish();
+
+        // In one go, and in jumps as when scrubbing a replay
+        let mut replayed = Simulation::new(recording.seed, recording.scenario.clone());
+        recording.play(&mut replayed, recording.ticks);
+        assert_eq!(replayed.metrics(), original.metrics());
+
+        let mut scrubbed = Simulation::new(recording.seed, recording.scenario.clone());
+        for tick in [1, 90, 91, 300, recording.ticks] {
+            recording.play(&mut scrubbed, tick);
+        }
+        assert_eq!(scrubbed.metrics(), original.metrics());
+    }
+}
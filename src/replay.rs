@@ -0,0 +1,182 @@
+use crate::car::draw_sprite;
+use crate::constants::*;
+use crate::geometry::Vec2;
+use crate::record::Recording;
+use crate::simulation::Simulation;
+use crate::text::draw_text;
+use crate::{draw_lines, draw_signal_heads};
+use sdl2::event::Event;
+use sdl2::image::LoadTexture;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::render::{Texture, WindowCanvas};
+use std::time::Instant;
+
+/// How far Left and Right jump through a replay, in seconds.
+const SCRUB_STEP: f32 = 5.0;
+
+/// Position in a recording. Runs are shown from their car snapshots when the
+/// recording has them, and otherwise re-simulated from the seed and inputs.
+struct Replay<'a> {
+    recording: &'a Recording,
+    tick: u64,
+    /// Re-run of the recording, when it has no snapshots.
+    simulation: Option<Simulation>,
+}
+
+impl<'a> Replay<'a> {
+    fn new(recording: &'a Recording) -> Self {
+        let simulation = (!recording.has_snapshots())
+            .then(|| Simulation::new(recording.seed, recording.scenario.clone()));
+        Self {
+            recording,
+            tick: 0,
+            simulation,
+        }
+    }
+
+    /// Jump to the end of `tick`. Going back re-runs the simulation from the start.
+    fn seek(&mut self, tick: u64) {
+        let recording = self.recording;
+        self.tick = tick.min(recording.ticks);
+        if let Some(simulation) = &mut self.simulation {
+            if self.tick < simulation.tick() {
+                *simulation = Simulation::new(recording.seed, recording.scenario.clone());
+            }
+            recording.play(simulation, self.tick);
+        }
+    }
+
+    fn at_end(&self) -> bool {
+        self.tick >= self.recording.ticks
+    }
+
+    fn draw(&self, canvas: &mut WindowCanvas, car_texture: &Texture) {
+        match &self.simulation {
+            Some(simulation) => {
+                draw_signal_heads(canvas, simulation);
+                for car in simulation.cars() {
+                    car.draw_all_components(canvas, car_texture, false)
+                        .expect("Failed to draw car");
+                }
+            }
+            None => {
+                let Some(snapshots) = self
+                    .tick
+                    .checked_sub(1)
+                    .and_then(|i| self.recording.snapshots.get(i as usize))
+                else {
+                    return;
+                };
+                // Every car has the size the recorded scenario gave it
+                let vehicles = &self.recording.scenario.vehicles;
+                let size = Vec2::new(vehicles.length, vehicles.width);
+                for car in snapshots {
+                    let center = Vec2::new(car.x, car.y);
+                    draw_sprite(canvas, car_texture, center, size, car.heading)
+                        .expect("Failed to draw car");
+                }
+            }
+        }
+    }
+}
+
+/// Play `recording` back in the window until ESC.
+///
+/// Space pauses and resumes, `.` steps one tick, Left and Right jump
+/// `SCRUB_STEP` seconds back or forward and Home goes back to the start.
+pub fn render_replay(
+    canvas: &mut WindowCanvas,
+    event_pump: &mut sdl2::EventPump,
+    ttf_context: &sdl2::ttf::Sdl2TtfContext,
+    recording: &Recording,
+) {
+    let mut replay = Replay::new(recording);
+    let mut paused = false;
+    let mut last_frame_time = Instant::now();
+    let mut accumulator = 0.0;
+    let scrub_ticks = (SCRUB_STEP / TICK_DURATION) as u64;
+
+    let texture_creator = canvas.texture_creator();
+    let car_texture = texture_creator
+        .load_texture("assets/car.png")
+        .expect("Could not load car texture");
+
+    'replay_loop: loop {
+        for event in event_pump.poll_iter() {
+            let Event::KeyDown {
+                keycode: Some(keycode),
+                ..
+            } = event
+            else {
+                if let Event::Quit { .. } = event {
+                    break 'replay_loop;
+                }
+                continue;
+            };
+            match keycode {
+                Keycode::Escape => break 'replay_loop,
+                Keycode::Space => paused = !paused,
+                Keycode::Period => {
+                    paused = true;
+                    replay.seek(replay.tick + 1);
+                }
+                Keycode::Left => replay.seek(replay.tick.saturating_sub(scrub_ticks)),
+                Keycode::Right => replay.seek(replay.tick + scrub_ticks),
+                Keycode::Home => replay.seek(0),
+                _ => {}
+            }
+        }
+
+        // Play at real time, stopping at the end of the recording
+        accumulator += last_frame_time.elapsed().as_secs_f32().min(MAX_FRAME_TIME);
+        last_frame_time = Instant::now();
+        while !paused && accumulator >= TICK_DURATION {
+            if replay.at_end() {
+                paused = true;
+                break;
+            }
+            replay.seek(replay.tick + 1);
+            accumulator -= TICK_DURATION;
+        }
+        if paused {
+            accumulator = 0.0;
+        }
+
+        canvas.set_draw_color(Color::RGB(0, 0, 0));
+        canvas.clear();
+        draw_lines(canvas, &recording.scenario.layout);
+        replay.draw(canvas, &car_texture);
+
+        let status = format!(
+            "Replay {:.1}s / {:.1}s (tick {} / {}){}",
+            replay.tick as f32 * TICK_DURATION,
+            recording.ticks as f32 * TICK_DURATION,
+            replay.tick,
+            recording.ticks,
+            if paused { ", paused" } else { "" },
+        );
+        for (i, line) in [
+            status.as_str(),
+            "Space: play/pause   .: step   Left/Right: -/+5s   Home: restart",
+        ]
+        .iter()
+        .enumerate()
+        {
+            draw_text(
+                &texture_creator,
+                "assets/Roboto-Regular.ttf",
+                16,
+                line,
+                Color::RGB(255, 255, 255),
+                10,
+                10 + 20 * i as i32,
+                canvas,
+                ttf_context,
+            )
+            .unwrap();
+        }
+
+        canvas.present();
+    }
+}
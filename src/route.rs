@@ -0,0 +1,249 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// Heading of a car on screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl Direction {
+    pub fn is_horizontal(self) -> bool {
+        match self {
+            Direction::East | Direction::West => true,
+            Direction::North | Direction::South => false,
+        }
+    }
+
+    pub fn is_perpendicular_to(self, other: Direction) -> bool {
+        self.is_horizontal() != other.is_horizontal()
+    }
+
+    /// Heading after making `turn` from this heading.
+    pub fn turned(self, turn: Turn) -> Direction {
+        match (self, turn) {
+            (_, Turn::Straight) => self,
+            (Direction::North, Turn::Left) | (Direction::South, Turn::Right) => Direction::West,
+            (Direction::North, Turn::Right) | (Direction::South, Turn::Left) => Direction::East,
+            (Direction::East, Turn::Left) | (Direction::West, Turn::Right) => Direction::North,
+            (Direction::East, Turn::Right) | (Direction::West, Turn::Left) => Direction::South,
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Direction::North => "North",
+            Direction::South => "South",
+            Direction::East => "East",
+            Direction::West => "West",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Side of the map a car enters from (the first letter of a route code).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Approach {
+    Right,
+    Down,
+    Left,
+    Up,
+}
+
+impl Approach {
+    pub const ALL: [Approach; 4] = [
+        Approach::Right,
+        Approach::Down,
+        Approach::Left,
+        Approach::Up,
+    ];
+
+    /// Heading of a car that has just entered from this side.
+    pub fn heading(self) -> Direction {
+        match self {
+            Approach::Right => Direction::West,
+            Approach::Down => Direction::North,
+            Approach::Left => Direction::East,
+            Approach::Up => Direction::South,
+        }
+    }
+
+    /// Side of the map cars heading `direction` come in from.
+    pub fn for_heading(direction: Direction) -> Approach {
+        match direction {
+            Direction::North => Approach::Down,
+            Direction::South => Approach::Up,
+            Direction::East => Approach::Left,
+            Direction::West => Approach::Right,
+        }
+    }
+
+    /// Side of the map a car heading `direction` drives off.
+    pub fn exited_by(direction: Direction) -> Approach {
+        match direction {
+            Direction::North => Approach::Up,
+            Direction::South => Approach::Down,
+            Direction::East => Approach::Right,
+            Direction::West => Approach::Left,
+        }
+    }
+
+    pub fn code(self) -> char {
+        match self {
+            Approach::Right => 'R',
+            Approach::Down => 'D',
+            Approach::Left => 'L',
+            Approach::Up => 'U',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Turn {
+    Left,
+    Straight,
+    Right,
+}
+
+impl Turn {
+    pub const ALL: [Turn; 3] = [Turn::Right, Turn::Straight, Turn::Left];
+}
+
+/// One of the 12 paths through the intersection, named by its behavior code:
+/// the side the car enters from, then the side it leaves by.
+/// `RU` enters on the right and leaves at the top.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Route {
+    RU,
+    RL,
+    RD,
+    DU,
+    DL,
+    DR,
+    LU,
+    LR,
+    LD,
+    UD,
+    UR,
+    UL,
+}
+
+impl Route {
+    pub const ALL: [Route; 12] = [
+        Route::RU,
+        Route::RL,
+        Route::RD,
+        Route::DU,
+        Route::DL,
+        Route::DR,
+        Route::LU,
+        Route::LR,
+        Route::LD,
+        Route::UD,
+        Route::UR,
+        Route::UL,
+    ];
+
+    pub fn new(approach: Approach, turn: Turn) -> Route {
+        match (approach, turn) {
+            (Approach::Right, Turn::Right) => Route::RU,
+            (Approach::Right, Turn::Straight) => Route::RL,
+            (Approach::Right, Turn::Left) => Route::RD,
+            (Approach::Down, Turn::Straight) => Route::DU,
+            (Approach::Down, Turn::Left) => Route::DL,
+            (Approach::Down, Turn::Right) => Route::DR,
+            (Approach::Left, Turn::Left) => Route::LU,
+            (Approach::Left, Turn::Straight) => Route::LR,
+            (Approach::Left, Turn::Right) => Route::LD,
+            (Approach::Up, Turn::Straight) => Route::UD,
+            (Approach::Up, Turn::Left) => Route::UR,
+            (Approach::Up, Turn::Right) => Route::UL,
+        }
+    }
+
+    pub fn approach(self) -> Approach {
+        match self {
+            Route::RU | Route::RL | Route::RD => Approach::Right,
+            Route::DU | Route::DL | Route::DR => Approach::Down,
+            Route::LU | Route::LR | Route::LD => Approach::Left,
+            Route::UD | Route::UR | Route::UL => Approach::Up,
+        }
+    }
+
+    pub fn turn(self) -> Turn {
+        match self {
+            Route::RU | Route::DR | Route::LD | Route::UL => Turn::Right,
+            Route::RL | Route::DU | Route::LR | Route::UD => Turn::Straight,
+            Route::RD | Route::DL | Route::LU | Route::UR => Turn::Left,
+        }
+    }
+
+    /// Side of the map the car leaves by.
+    pub fn exit(self) -> Approach {
+        Approach::exited_by(self.final_direction())
+    }
+
+    pub fn initial_direction(self) -> Direction {
+        self.approach().heading()
+    }
+
+    pub fn final_direction(self) -> Direction {
+        self.initial_direction().turned(self.turn())
+    }
+
+    /// Index of the grid line on the near side of this route's entry lane.
+    /// Each approach has three lanes, one per turn.
+    pub fn lane(self) -> i32 {
+        match self {
+            Route::RU | Route::UL => 4,
+            Route::RL | Route::UD => 5,
+            Route::RD | Route::UR => 6,
+            Route::DL | Route::LU => 7,
+            Route::DU | Route::LR => 8,
+            Route::DR | Route::LD => 9,
+        }
+    }
+
+    /// Index of the grid line on the near side of the lane the car leaves by:
+    /// the entry lane that makes the same turn in the new heading.
+    pub fn exit_lane(self) -> i32 {
+        Route::new(Approach::for_heading(self.final_direction()), self.turn()).lane()
+    }
+}
+
+impl fmt::Display for Route {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.approach().code(), self.exit().code())
+    }
+}
+
+/// Routes are written by their behavior code, like `RU`.
+impl Serialize for Route {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Route {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        code.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl FromStr for Route {
+    type Err = String;
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        Route::ALL
+            .into_iter()
+            .find(|route| route.to_string() == code)
+            .ok_or_else(|| format!("Unknown route code: {code}"))
+    }
+}
@@ -0,0 +1,405 @@
+use crate::car::Car;
+use crate::constants::*;
+use crate::geometry::{FRect, OrientedRect, Vec2};
+use crate::reservation::{tiles_under, Tile, GRID_SIZE};
+use crate::route::Route;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Time between the projected positions checked for time-to-collision, in seconds.
+const PROJECTION_STEP: f32 = 0.05;
+
+/// When two cars count as having had a close call.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SafetyThresholds {
+    /// Flag cars that would touch, or come within `safety_distance`, within
+    /// this many seconds if both kept their speed along their paths.
+    pub time_to_collision: f32,
+    /// Flag a car entering a patch of the intersection less than this many
+    /// seconds after a car on another route left it.
+    pub post_encroachment_time: f32,
+    /// Gap between projected car bodies that counts as a near miss, in pixels.
+    pub safety_distance: f32,
+}
+
+impl Default for SafetyThresholds {
+    fn default() -> Self {
+        Self {
+            time_to_collision: 0.75,
+            post_encroachment_time: 1.0,
+            safety_distance: 3.0,
+        }
+    }
+}
+
+/// How a close call was detected, with the measured time in seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CloseCallKind {
+    TimeToCollision(f32),
+    PostEncroachmentTime(f32),
+}
+
+/// Two cars that came closer than the thresholds allow without colliding.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CloseCall {
+    pub tick: u64,
+    pub kind: CloseCallKind,
+    pub car_ids: (u32, u32),
+    pub routes: (Route, Route),
+    /// Midpoint between the two cars when the close call was flagged.
+    pub position: Vec2,
+}
+
+/// Surrogate safety measures, checked once per tick.
+///
+/// Time-to-collision projects every pair of cars forward along their paths
+/// at their current speeds. Post-encroachment time watches the 6x6 tiles of
+/// the core intersection for a car entering a tile shortly after a car on a
+/// different route left it, which catches crossings that TTC misses because
+/// the cars never head for each other at the same time.
+///
+/// Each pair of cars is flagged at most once, by whichever measure trips first.
+/// A TTC encounter is reported when it's over, with the lowest TTC it reached.
+/// Pairs and tiles are kept in ordered maps so close calls come out in the
+/// same order on every run of a seed.
+pub struct SafetyAnalyzer {
+    thresholds: SafetyThresholds,
+    flagged: HashSet<(u32, u32)>,
+    /// TTC encounters still going on, with the lowest TTC so far.
+    encounters: BTreeMap<(u32, u32), (f32, CloseCall)>,
+    /// Cars in each tile on the previous tick.
+    occupants: BTreeMap<Tile, Vec<(u32, Route)>>,
+    /// Last car to leave each tile, and the tick it left on.
+    last_left: BTreeMap<Tile, (u32, Route, u64)>,
+}
+
+impl SafetyAnalyzer {
+    pub fn new(thresholds: SafetyThresholds) -> Self {
+        Self {
+            thresholds,
+            flagged: HashSet::new(),
+            encounters: BTreeMap::new(),
+            occupants: BTreeMap::new(),
+            last_left: BTreeMap::new(),
+        }
+    }
+
+    /// Return the close calls settled on `tick`: PET ones as they happen
+    /// and TTC ones once the cars stop closing in on each other.
+    pub fn detect(&mut self, tick: u64, cars: &[Car], core_intersection: &FRect) -> Vec<CloseCall> {
+        // Forget pairs where a car has left the map
+        let present: HashSet<u32> = cars.iter().map(|car| car.id).collect();
+        self.flagged
+            .retain(|(a, b)| present.contains(a) && present.contains(b));
+
+        let mut close_calls = Vec::new();
+        let by_id: HashMap<u32, &Car> = cars.iter().map(|car| (car.id, car)).collect();
+
+        // Time-to-collision between every pair
+        let mut closing = HashSet::new();
+        for (i, car) in cars.iter().enumerate() {
+            for other in &cars[i + 1..] {
+                let pair = (car.id.min(other.id), car.id.max(other.id));
+                if self.flagged.contains(&pair) {
+                    continue;
+                }
+                let Some(ttc) = self.time_to_collision(car, other) else {
+                    continue;
+                };
+                closing.insert(pair);
+                let lowest = self
+                    .encounters
+                    .get(&pair)
+                    .map_or(f32::MAX, |&(lowest, _)| lowest);
+                if ttc < lowest {
+                    let close_call =
+                        close_call(tick, CloseCallKind::TimeToCollision(ttc), car, other);
+                    self.encounters.insert(pair, (ttc, close_call));
+                }
+            }
+        }
+        let ended: Vec<(u32, u32)> = self
+            .encounters
+            .keys()
+            .filter(|pair| !closing.contains(pair))
+            .copied()
+            .collect();
+        for pair in ended {
+            if let Some((_, close_call)) = self.encounters.remove(&pair) {
+                self.flag(close_call, &mut close_calls);
+            }
+        }
+
+        // Post-encroachment time on each tile of the intersection
+        let mut occupants: BTreeMap<Tile, Vec<(u32, Route)>> = BTreeMap::new();
+        for car in cars {
+            for tile in tiles_covered(car, core_intersection) {
+                occupants.entry(tile).or_default().push((car.id, car.route));
+            }
+        }
+        for (tile, before) in &self.occupants {
+            let now = occupants.get(tile);
+            for &(id, route) in before {
+                if now.is_none_or(|now| !now.iter().any(|&(other, _)| other == id)) {
+                    self.last_left.insert(*tile, (id, route, tick));
+                }
+            }
+        }
+        let pet_ticks = (self.thresholds.post_encroachment_time / TICK_DURATION) as u64;
+        let mut encroachments = Vec::new();
+        for (tile, now) in &occupants {
+            let before = self.occupants.get(tile);
+            for &(id, route) in now {
+                if before.is_some_and(|before| before.iter().any(|&(other, _)| other == id)) {
+                    continue;
+                }
+                // Just entered: anyone from another route here now, or recently?
+                let sharing = now
+                    .iter()
+                    .find(|&&(other, other_route)| other != id && other_route != route)
+                    .map(|&(other, _)| (other, 0.0));
+                let recent = self
+                    .last_left
+                    .get(tile)
+                    .and_then(|&(other, other_route, left)| {
+                        (other != id && other_route != route && tick - left <= pet_ticks)
+                            .then(|| (other, (tick - left) as f32 * TICK_DURATION))
+                    });
+                if let Some((other, pet)) = sharing.or(recent) {
+                    encroachments.push((id, other, pet));
+                }
+            }
+        }
+        self.occupants = occupants;
+        for (id, other, pet) in encroachments {
+            if let (Some(car), Some(other)) = (by_id.get(&id), by_id.get(&other)) {
+                let close_call =
+                    close_call(tick, CloseCallKind::PostEncroachmentTime(pet), car, other);
+                self.flag(close_call, &mut close_calls);
+            }
+        }
+
+        close_calls
+    }
+
+    /// Seconds until the two cars would touch or pass within the safety
+    /// distance if both kept their speed, if that's within the threshold.
+    /// Cars already touching are a collision, not a close call.
+    fn time_to_collision(&self, car: &Car, other: &Car) -> Option<f32> {
+        // Too far apart to close the gap in time, even head-on
+        let reach = (car.current_speed + other.current_speed) * self.thresholds.time_to_collision
+            + car.body.size.x.hypot(car.body.size.y)
+            + self.thresholds.safety_distance;
+        if car.body.center.distance(other.body.center) > reach {
+            return None;
+        }
+        let distance_now = car.body.distance(&other.body);
+        if distance_now <= 0.0 {
+            return None;
+        }
+
+        let project = |car: &Car, time: f32| {
+            let pose = car.path.pose_at(car.distance + car.current_speed * time);
+            OrientedRect::new(pose.position, car.body.size, pose.heading)
+        };
+        let steps = (self.thresholds.time_to_collision / PROJECTION_STEP) as u32;
+        (1..=steps)
+            .map(|step| step as f32 * PROJECTION_STEP)
+            .find(|&time| {
+                let distance = project(car, time).distance(&project(other, time));
+                distance <= self.thresholds.safety_distance && distance < distance_now
+            })
+    }
+
+    /// Report `close_call` unless its pair of cars was already flagged.
+    fn flag(&mut self, close_call: CloseCall, close_calls: &mut Vec<CloseCall>) {
+        let (a, b) = close_call.car_ids;
+        if self.flagged.insert((a.min(b), a.max(b))) {
+            self.encounters.remove(&(a.min(b), a.max(b)));
+            close_calls.push(close_call);
+        }
+    }
+}
+
+fn close_call(tick: u64, kind: CloseCallKind, car: &Car, other: &Car) -> CloseCall {
+    CloseCall {
+        tick,
+        kind,
+        car_ids: (car.id, other.id),
+        routes: (car.route, other.route),
+        position: Vec2::new(
+            (car.body.center.x + other.body.center.x) / 2.0,
+            (car.body.center.y + other.body.center.y) / 2.0,
+        ),
+    }
+}
+
+/// Tiles of the core intersection the car body actually overlaps.
+fn tiles_covered(car: &Car, core_intersection: &FRect) -> Vec<Tile> {
+    let Some(covered) = car.car_rect.intersect(*core_intersection) else {
+        return Vec::new();
+    };
+    let tile_size = core_intersection.w / GRID_SIZE as f32;
+    tiles_under(&covered, core_intersection)
+        .into_iter()
+        .filter(|&(column, row)| {
+            let tile = FRect::new(
+                core_intersection.x + column as f32 * tile_size,
+                core_intersection.y + row as f32 * tile_size,
+                tile_size,
+                tile_size,
+            );
+            car.body.intersects(&OrientedRect::from(tile))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::car::car_at;
+    use crate::scenario::Scenario;
+
+    struct Road {
+        scenario: Scenario,
+        analyzer: SafetyAnalyzer,
+        tick: u64,
+    }
+
+    impl Road {
+        fn new() -> Self {
+            let scenario = Scenario::default();
+            let analyzer = SafetyAnalyzer::new(scenario.safety_thresholds);
+            Self {
+                scenario,
+                analyzer,
+                tick: 0,
+            }
+        }
+
+        fn line_spacing(&self) -> f32 {
+            self.scenario.layout.line_spacing() as f32
+        }
+
+        /// A car on `route` with its center `along` px from the window edge
+        /// it enters by, at `speed`.
+        fn car(&self, id: u32, route: Route, along: f32, speed: f32) -> Car {
+            // Paths start one car length off the window
+            let distance = along + self.scenario.vehicles.length;
+            car_at(id, route, &self.scenario, distance, speed)
+        }
+
+        /// Run the next tick's detection on `cars`.
+        fn detect(&mut self, cars: &[Car]) -> Vec<CloseCall> {
+            self.tick += 1;
+            let core_intersection = self.scenario.layout.core_intersection();
+            self.analyzer.detect(self.tick, cars, &core_intersection)
+        }
+    }
+
+    const EAST: Route = Route::LR;
+    const SOUTH: Route = Route::UD;
+
+    /// A stopped car on the way in from the left, and one closing in on it
+    /// from behind at `speed` with `gap` px between them.
+    fn closing(road: &Road, gap: f32, speed: f32) -> Vec<Car> {
+        let length = road.scenario.vehicles.length;
+        let leader = 2.0 * road.line_spacing();
+        vec![
+            road.car(0, EAST, leader, 0.0),
+            road.car(1, EAST, leader - length - gap, speed),
+        ]
+    }
+
+    #[test]
+    fn time_to_collision_below_and_above_threshold() {
+        // Touching within the safety distance in 0.27 s
+        let mut road = Road::new();
+        assert!(road.detect(&closing(&road, 30.0, 100.0)).is_empty());
+        let close_calls = road.detect(&closing(&road, 30.0, 0.0));
+        assert_eq!(close_calls.len(), 1);
+        assert_eq!(close_calls[0].car_ids, (0, 1));
+
+        // 0.97 s away, beyond the 0.75 s threshold
+        let mut road = Road::new();
+        assert!(road.detect(&closing(&road, 100.0, 100.0)).is_empty());
+        assert!(road.detect(&closing(&road, 100.0, 0.0)).is_empty());
+    }
+
+    #[test]
+    fn time_to_collision_is_reported_once_with_its_lowest_value() {
+        let mut road = Road::new();
+        for gap in [60.0, 30.0, 45.0] {
+            assert!(road.detect(&closing(&road, gap, 100.0)).is_empty());
+        }
+        let close_calls = road.detect(&closing(&road, 45.0, 0.0));
+        assert_eq!(close_calls.len(), 1);
+        let CloseCallKind::TimeToCollision(ttc) = close_calls[0].kind else {
+            panic!("expected a TTC close call, got {:?}", close_calls[0].kind);
+        };
+        // (30 - 3) px at 100 px/s, found in steps of PROJECTION_STEP
+        assert!((ttc - 0.3).abs() < PROJECTION_STEP / 2.0, "ttc {ttc}");
+
+        // The pair stays flagged
+        assert!(road.detect(&closing(&road, 10.0, 100.0)).is_empty());
+        assert!(road.detect(&closing(&road, 10.0, 0.0)).is_empty());
+    }
+
+    /// Crossing lanes meet on the tile in column 1, row 4 of the core
+    /// intersection: the east-bound lane 8 and the south-bound lane 5.
+    #[test]
+    fn post_encroachment_on_a_shared_tile() {
+        let mut road = Road::new();
+        let ls = road.line_spacing();
+        let crossing = 5.5 * ls;
+        let waiting = 3.0 * ls;
+
+        // The east-bound car crosses the tile, then the south-bound car
+        // enters it a tick after it left
+        assert!(road
+            .detect(&[
+                road.car(0, EAST, crossing, 0.0),
+                road.car(1, SOUTH, waiting, 0.0),
+            ])
+            .is_empty());
+        assert!(road
+            .detect(&[
+                road.car(0, EAST, crossing + 2.0 * ls, 0.0),
+                road.car(1, SOUTH, waiting, 0.0),
+            ])
+            .is_empty());
+        let close_calls = road.detect(&[
+            road.car(0, EAST, crossing + 2.0 * ls, 0.0),
+            road.car(1, SOUTH, 8.5 * ls, 0.0),
+        ]);
+        assert_eq!(close_calls.len(), 1);
+        assert_eq!(close_calls[0].car_ids, (1, 0));
+        assert_eq!(close_calls[0].routes, (SOUTH, EAST));
+        assert_eq!(
+            close_calls[0].kind,
+            CloseCallKind::PostEncroachmentTime(TICK_DURATION)
+        );
+    }
+
+    #[test]
+    fn followers_on_the_same_route_are_not_encroaching() {
+        let mut road = Road::new();
+        let ls = road.line_spacing();
+        let crossing = 5.5 * ls;
+        let follower = 2.0 * ls;
+
+        for (leader, follower) in [
+            (crossing, follower),
+            (crossing + 2.0 * ls, follower),
+            (crossing + 2.0 * ls, crossing),
+        ] {
+            assert!(road
+                .detect(&[
+                    road.car(0, EAST, leader, 0.0),
+                    road.car(1, EAST, follower, 0.0),
+                ])
+                .is_empty());
+        }
+    }
+}
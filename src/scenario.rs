@@ -0,0 +1,356 @@
+use crate::cli::ScenarioArgs;
+use crate::demand::Demand;
+use crate::geometry::{FRect, OrientedRect, Vec2};
+use crate::idm::IdmParams;
+use crate::policy::PolicyKind;
+use crate::route::Approach;
+use crate::safety::SafetyThresholds;
+use crate::signal::SignalPlan;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Size of the map. The window is split into a 14x14 grid of lanes, with the
+/// 6x6-lane core intersection in the middle.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Layout {
+    /// Width and height of the window, in pixels.
+    pub window_size: u32,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self { window_size: 800 }
+    }
+}
+
+impl Layout {
+    /// Width of a lane, in pixels.
+    pub fn line_spacing(&self) -> i32 {
+        (self.window_size / 14) as i32
+    }
+
+    /// The 6x6-lane box in the middle of the map.
+    pub fn core_intersection(&self) -> FRect {
+        let line_spacing = self.line_spacing() as f32;
+        FRect::new(
+            4.0 * line_spacing,
+            4.0 * line_spacing,
+            6.0 * line_spacing,
+            6.0 * line_spacing,
+        )
+    }
+}
+
+/// What the cars look like and how fast they want to go.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Vehicles {
+    /// Car body length, in pixels.
+    pub length: f32,
+    /// Car body width, in pixels.
+    pub width: f32,
+    /// How far ahead of its front bumper a car watches for others, in pixels.
+    pub radar_length: f32,
+    /// Cruising speeds are rolled between these two, in px/s.
+    pub min_speed: f32,
+    pub max_speed: f32,
+    /// Spawns are refused once this many cars are on the road.
+    pub max_cars: usize,
+}
+
+impl Default for Vehicles {
+    fn default() -> Self {
+        Self {
+            length: 43.0,
+            width: 33.0,
+            // Four car lengths, enough to brake comfortably from top speed
+            radar_length: 172.0,
+            min_speed: 48.0,
+            max_speed: 120.0,
+            max_cars: 9999,
+        }
+    }
+}
+
+/// Everything besides the seed that decides how a run plays out.
+/// Loaded from a TOML or JSON file with `--scenario`; every field is optional
+/// and falls back to its default.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Scenario {
+    pub policy: PolicyKind,
+    /// Most left-turning cars the heuristic policy lets into the box at once.
+    pub max_left_turners: usize,
+    pub layout: Layout,
+    pub vehicles: Vehicles,
+    pub demand: Demand,
+    /// Only used by the signal policy.
+    pub signal_plan: SignalPlan,
+    /// Driving behavior of every car, apart from its desired speed.
+    pub driver: IdmParams,
+    pub safety_thresholds: SafetyThresholds,
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Self {
+            policy: PolicyKind::default(),
+            max_left_turners: 3,
+            layout: Layout::default(),
+            vehicles: Vehicles::default(),
+            demand: Demand::default(),
+            signal_plan: SignalPlan::default(),
+            driver: IdmParams::default(),
+            safety_thresholds: SafetyThresholds::default(),
+        }
+    }
+}
+
+impl Scenario {
+    /// Read a scenario from a `.toml` or `.json` file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        let scenario: Scenario = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => toml::from_str(&text).map_err(|e| e.to_string()),
+            Some("json") => serde_json::from_str(&text).map_err(|e| e.to_string()),
+            _ => Err("expected a .toml or .json file".to_string()),
+        }
+        .map_err(|e| format!("Invalid scenario {}: {e}", path.display()))?;
+        scenario
+            .validate()
+            .map_err(|e| format!("Invalid scenario {}: {e}", path.display()))?;
+        Ok(scenario)
+    }
+
+    /// The scenario given on the command line: the `--scenario` file if
+    /// there is one, or the defaults tuned by the other flags.
+    pub fn from_args(args: &ScenarioArgs) -> Result<Self, String> {
+        let mut scenario = match &args.scenario {
+            Some(path) => Scenario::load(path)?,
+            None => Self {
+                signal_plan: SignalPlan::new(
+                    args.signal_green,
+                    args.signal_left_green,
+                    args.signal_yellow,
+                    args.signal_all_red,
+                ),
+                driver: IdmParams {
+                    time_headway: args.time_headway,
+                    min_gap: args.min_gap,
+                    max_acceleration: args.max_acceleration,
+                    comfortable_deceleration: args.comfortable_deceleration,
+                    max_deceleration: args.max_deceleration,
+                    ..IdmParams::default()
+                },
+                safety_thresholds: SafetyThresholds {
+                    time_to_collision: args.ttc_threshold,
+                    post_encroachment_time: args.pet_threshold,
+                    safety_distance: args.safety_distance,
+                },
+                ..Self::default()
+            },
+        };
+        scenario.override_with(args.policy, args.window_size);
+        scenario
+            .validate()
+            .map_err(|e| format!("Invalid scenario: {e}"))?;
+        Ok(scenario)
+    }
+
+    /// Check for values the simulation can't run with.
+    pub fn validate(&self) -> Result<(), String> {
+        let check = |valid: bool, problem: &str| valid.then_some(()).ok_or(problem.to_string());
+
+        let vehicles = &self.vehicles;
+        check(
+            vehicles.length > 0.0 && vehicles.width > 0.0,
+            "vehicles.length and vehicles.width must be positive",
+        )?;
+        check(
+            vehicles.radar_length >= 0.0,
+            "vehicles.radar_length must not be negative",
+        )?;
+        check(
+            vehicles.min_speed > 0.0,
+            "vehicles.min_speed must be positive",
+        )?;
+        check(
+            vehicles.max_speed >= vehicles.min_speed,
+            "vehicles.max_speed must not be below vehicles.min_speed",
+        )?;
+
+        let line_spacing = self.layout.line_spacing() as f32;
+        if line_spacing < vehicles.width {
+            return Err(format!(
+                "layout.window_size {} makes lanes {line_spacing} px wide, too narrow for cars {} px wide",
+                self.layout.window_size, vehicles.width
+            ));
+        }
+        if !right_turn_fits(line_spacing, Vec2::new(vehicles.length, vehicles.width)) {
+            return Err(format!(
+                "layout.window_size {} makes lanes {line_spacing} px wide, too narrow for cars {} px long to turn right",
+                self.layout.window_size, vehicles.length
+            ));
+        }
+
+        let driver = &self.driver;
+        check(
+            driver.time_headway >= 0.0 && driver.min_gap >= 0.0,
+            "driver.time_headway and driver.min_gap must not be negative",
+        )?;
+        check(
+            driver.max_acceleration > 0.0,
+            "driver.max_acceleration must be positive",
+        )?;
+        check(
+            driver.comfortable_deceleration > 0.0,
+            "driver.comfortable_deceleration must be positive",
+        )?;
+        check(
+            driver.max_deceleration >= driver.comfortable_deceleration,
+            "driver.max_deceleration must not be below driver.comfortable_deceleration",
+        )?;
+
+        let demand = &self.demand;
+        check(
+            demand.spawn_delay > 0.0,
+            "demand.spawn_delay must be positive",
+        )?;
+        check(
+            demand
+                .profile
+                .windows(2)
+                .all(|pair| pair[0].time < pair[1].time),
+            "demand.profile points must be in increasing order of time",
+        )?;
+        check(
+            demand.profile.iter().all(|point| point.factor >= 0.0),
+            "demand.profile factors must not be negative",
+        )?;
+        check(
+            Approach::ALL
+                .into_iter()
+                .all(|approach| demand.rates.get(approach) >= 0.0),
+            "demand.rates must not be negative",
+        )?;
+        check(
+            demand.od.values().all(|&weight| weight >= 0.0),
+            "demand.od weights must not be negative",
+        )?;
+        check(
+            demand.od.is_empty() || demand.od.values().any(|&weight| weight > 0.0),
+            "demand.od must give at least one route a positive weight",
+        )?;
+        Ok(())
+    }
+
+    /// Switch to `policy` and `window_size`, where given.
+    pub fn override_with(&mut self, policy: Option<PolicyKind>, window_size: Option<u32>) {
+        if let Some(policy) = policy {
+            self.policy = policy;
+        }
+        if let Some(window_size) = window_size {
+            self.layout.window_size = window_size;
+        }
+    }
+}
+
+/// Whether a car of `size` turning right, on an arc of half a lane's radius,
+/// keeps its rear out of a car waiting at the stop line in the next lane.
+///
+/// Laid out heading East from a stop line at x = 0 and turning South, with
+/// the waiting car one lane to the North.
+fn right_turn_fits(line_spacing: f32, size: Vec2) -> bool {
+    let radius = line_spacing / 2.0;
+    let waiting = OrientedRect::new(Vec2::new(-size.x / 2.0, -line_spacing), size, 0.0);
+    (0..=90).all(|degrees| {
+        let angle = (degrees as f32).to_radians();
+        let center = Vec2::new(radius * angle.sin(), radius * (1.0 - angle.cos()));
+        !OrientedRect::new(center, size, angle).intersects(&waiting)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::demand::ProfilePoint;
+    use crate::headless;
+    use crate::route::{Route, Turn};
+    use crate::simulation::Simulation;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn shipped_scenarios_are_valid() {
+        for path in ["scenarios/default.toml", "scenarios/rush_hour.toml"] {
+            Scenario::load(Path::new(path)).unwrap();
+        }
+        Scenario::default().validate().unwrap();
+    }
+
+    #[test]
+    fn rejects_values_the_simulation_cannot_run_with() {
+        let invalid = [
+            |s: &mut Scenario| s.vehicles.min_speed = 0.0,
+            |s: &mut Scenario| s.vehicles.max_speed = s.vehicles.min_speed - 1.0,
+            |s: &mut Scenario| s.driver.comfortable_deceleration = -90.0,
+            |s: &mut Scenario| s.driver.max_deceleration = s.driver.comfortable_deceleration / 2.0,
+            |s: &mut Scenario| s.layout.window_size = 100,
+            |s: &mut Scenario| s.demand.rates.left = -1.0,
+            |s: &mut Scenario| {
+                s.demand.od = BTreeMap::from([(Route::new(Approach::Right, Turn::Left), -1.0)])
+            },
+            |s: &mut Scenario| {
+                s.demand.od = BTreeMap::from([
+                    (Route::new(Approach::Right, Turn::Left), 0.0),
+                    (Route::new(Approach::Up, Turn::Straight), 0.0),
+                ])
+            },
+            |s: &mut Scenario| {
+                s.demand.profile = vec![
+                    ProfilePoint {
+                        time: 60.0,
+                        factor: 1.0,
+                    },
+                    ProfilePoint {
+                        time: 0.0,
+                        factor: 2.0,
+                    },
+                ]
+            },
+        ];
+        for (i, break_it) in invalid.iter().enumerate() {
+            let mut scenario = Scenario::default();
+            break_it(&mut scenario);
+            assert!(scenario.validate().is_err(), "case {i} passed validation");
+        }
+    }
+
+    #[test]
+    fn lanes_must_fit_a_right_turn() {
+        // Lanes of 38 px let the rear of a turning car swing into the next
+        // lane; 39 px is the smallest that runs without collisions
+        let mut scenario = Scenario::default();
+        scenario.layout.window_size = 38 * 14;
+        assert!(scenario.validate().is_err());
+        scenario.layout.window_size = 39 * 14;
+        scenario.validate().unwrap();
+    }
+
+    #[test]
+    fn accepts_a_fixed_cruising_speed() {
+        let mut scenario = Scenario::default();
+        scenario.vehicles.min_speed = 90.0;
+        scenario.vehicles.max_speed = 90.0;
+        scenario.validate().unwrap();
+
+        let mut simulation = Simulation::new(1, scenario);
+        headless::simulate(&mut simulation, 60);
+        assert!(simulation
+            .cars()
+            .iter()
+            .all(|car| car.driver.desired_speed == 90.0));
+    }
+}
@@ -0,0 +1,147 @@
+use crate::car::Car;
+use crate::constants::*;
+use crate::geometry::FRect;
+use crate::policy::IntersectionPolicy;
+use crate::route::{Route, Turn};
+use serde::{Deserialize, Serialize};
+
+/// Aspect shown by a signal head.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignalColor {
+    Green,
+    Yellow,
+    Red,
+}
+
+/// One stage of the signal cycle: the routes that get green together.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignalPhase {
+    pub routes: Vec<Route>,
+    /// Seconds of green, followed by the plan's yellow and all-red times.
+    pub green: f32,
+}
+
+/// Fixed-time signal plan, cycled through in order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SignalPlan {
+    pub phases: Vec<SignalPhase>,
+    pub yellow: f32,
+    pub all_red: f32,
+    /// Right turns use the corner lanes and never cross other traffic,
+    /// so they may go on red.
+    pub free_right_turns: bool,
+}
+
+/// The standard plan with 12s through greens, 6s protected lefts,
+/// 3s yellow and 3s all-red.
+impl Default for SignalPlan {
+    fn default() -> Self {
+        SignalPlan::new(12.0, 6.0, 3.0, 3.0)
+    }
+}
+
+impl SignalPlan {
+    /// The standard plan: a through phase for each axis, each followed by a
+    /// protected left-turn phase per approach so opposing lefts never meet.
+    pub fn new(through_green: f32, left_green: f32, yellow: f32, all_red: f32) -> Self {
+        let phase = |routes: &[Route], green: f32| SignalPhase {
+            routes: routes.to_vec(),
+            green,
+        };
+        Self {
+            phases: vec![
+                phase(&[Route::DU, Route::UD], through_green),
+                phase(&[Route::DU, Route::DL], left_green),
+                phase(&[Route::UD, Route::UR], left_green),
+                phase(&[Route::RL, Route::LR], through_green),
+                phase(&[Route::RL, Route::RD], left_green),
+                phase(&[Route::LR, Route::LU], left_green),
+            ],
+            yellow,
+            all_red,
+            free_right_turns: true,
+        }
+    }
+
+    fn phase_length(&self, phase: &SignalPhase) -> f32 {
+        phase.green + self.yellow + self.all_red
+    }
+
+    fn cycle_length(&self) -> f32 {
+        self.phases
+            .iter()
+            .map(|phase| self.phase_length(phase))
+            .sum()
+    }
+}
+
+/// Conventional signal-controlled intersection, used as a baseline for the
+/// autonomous policies. Cars may only enter on green, or on yellow if they
+/// are too close to stop comfortably.
+pub struct SignalPolicy {
+    plan: SignalPlan,
+    time: f32,
+}
+
+impl SignalPolicy {
+    pub fn new(plan: SignalPlan) -> Self {
+        Self { plan, time: 0.0 }
+    }
+}
+
+impl IntersectionPolicy for SignalPolicy {
+    fn name(&self) -> &'static str {
+        "signal"
+    }
+
+    fn begin_tick(&mut self, tick: u64, _cars: &[Car]) {
+        self.time = tick as f32 * TICK_DURATION;
+    }
+
+    fn may_enter(&mut self, car: &Car, _cars: &[Car], core_intersection: &FRect) -> bool {
+        match self.signal(car.route) {
+            Some(SignalColor::Green) => true,
+            // Go through on yellow when too close to stop comfortably
+            Some(SignalColor::Yellow) => {
+                let stop_line = (car.distance_to_stop_line(core_intersection), 0.0);
+                car.driver.acceleration(car.current_speed, Some(stop_line))
+                    < -car.driver.comfortable_deceleration
+            }
+            _ => false,
+        }
+    }
+
+    fn signal(&self, route: Route) -> Option<SignalColor> {
+        if self.plan.free_right_turns && route.turn() == Turn::Right {
+            return Some(SignalColor::Green);
+        }
+
+        let cycle_length = self.plan.cycle_length();
+        if self.plan.phases.is_empty() || cycle_length <= 0.0 {
+            return Some(SignalColor::Red);
+        }
+
+        // Find the phase we're in and how far into it
+        let mut into_phase = self.time % cycle_length;
+        for (index, phase) in self.plan.phases.iter().enumerate() {
+            let phase_length = self.plan.phase_length(phase);
+            if into_phase < phase_length {
+                // Routes that keep green in the next phase skip yellow and all-red
+                let next_phase = &self.plan.phases[(index + 1) % self.plan.phases.len()];
+                let color = if !phase.routes.contains(&route) {
+                    SignalColor::Red
+                } else if into_phase < phase.green || next_phase.routes.contains(&route) {
+                    SignalColor::Green
+                } else if into_phase < phase.green + self.plan.yellow {
+                    SignalColor::Yellow
+                } else {
+                    SignalColor::Red
+                };
+                return Some(color);
+            }
+            into_phase -= phase_length;
+        }
+        Some(SignalColor::Red)
+    }
+}
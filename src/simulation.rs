@@ -0,0 +1,448 @@
+use crate::car::{check_perpendicular_and_move_back, Car, IntersectionState};
+use crate::collision::CollisionDetector;
+use crate::constants::*;
+use crate::demand::DemandGenerator;
+use crate::event::{SimEvent, Subscriber};
+use crate::geometry::{FRect, OrientedRect};
+use crate::metrics::{Metrics, MetricsCollector, TickSample};
+use crate::policy::IntersectionPolicy;
+use crate::route::{Approach, Route, Turn};
+use crate::safety::SafetyAnalyzer;
+use crate::scenario::Scenario;
+use crate::signal::SignalColor;
+use crate::trip::{TripRecord, WAITING_SPEED};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+/// Something done to the simulation from outside, like a key press.
+/// Recordings store these so a run can be replayed exactly.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SimInput {
+    /// Spawn a car entering from the approach, with a random turn.
+    SpawnFrom(Approach),
+    ToggleRandomGeneration,
+}
+
+/// Headless intersection simulation.
+///
+/// Owns every car on the road and advances them one step at a time, without
+/// touching SDL2. The windowed front-end in `main.rs` is just one consumer:
+/// it feeds input into `apply` and draws whatever `cars()` returns.
+///
+/// All randomness comes from a single RNG seeded in `new`, so two simulations
+/// with the same seed, scenario and inputs produce the same run.
+pub struct Simulation {
+    cars: Vec<Car>,
+    core_intersection: FRect,
+    next_id: u32,
+    is_random_generation: bool,
+    demand: DemandGenerator,
+    /// Ticks on which the cars waiting to enter each lane arrived, oldest
+    /// first. Arrivals queue up here, off the map, while their spawn area
+    /// is blocked.
+    entry_queues: BTreeMap<Route, VecDeque<u64>>,
+    tick: u64,
+    /// Cars that have left the map so far.
+    completed: u32,
+    seed: u64,
+    rng: StdRng,
+    collision_detector: CollisionDetector,
+    safety: SafetyAnalyzer,
+    metrics: MetricsCollector,
+    subscribers: Vec<Box<dyn Subscriber>>,
+    /// Events emitted since the last `step` started.
+    events: Vec<SimEvent>,
+    policy: Box<dyn IntersectionPolicy>,
+    scenario: Scenario,
+}
+
+impl Simulation {
+    pub fn new(seed: u64, scenario: Scenario) -> Self {
+        Self {
+            cars: Vec::new(),
+            core_intersection: scenario.layout.core_intersection(),
+            next_id: 0,
+            is_random_generation: scenario.demand.random_generation,
+            demand: DemandGenerator::default(),
+            entry_queues: BTreeMap::new(),
+            tick: 0,
+            completed: 0,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            collision_detector: CollisionDetector::default(),
+            safety: SafetyAnalyzer::new(scenario.safety_thresholds),
+            metrics: MetricsCollector::default(),
+            subscribers: Vec::new(),
+            events: Vec::new(),
+            policy: scenario.policy.build(&scenario),
+            scenario,
+        }
+    }
+
+    /// The scenario the simulation was created with.
+    pub fn scenario(&self) -> &Scenario {
+        &self.scenario
+    }
+
+    /// Metrics gathered since the simulation was created.
+    pub fn metrics(&self) -> &Metrics {
+        self.metrics.metrics()
+    }
+
+    /// Register `subscriber` to receive every event from now on.
+    pub fn subscribe(&mut self, subscriber: Box<dyn Subscriber>) {
+        self.subscribers.push(subscriber);
+    }
+
+    /// Events of the last `step`, and of any spawns since.
+    pub fn events(&self) -> &[SimEvent] {
+        &self.events
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Number of ticks simulated so far.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Name of the intersection-management policy in use.
+    pub fn policy_name(&self) -> &'static str {
+        self.policy.name()
+    }
+
+    /// Signal currently shown to `route`, if the policy uses traffic lights.
+    pub fn signal(&self, route: Route) -> Option<SignalColor> {
+        self.policy.signal(route)
+    }
+
+    /// Cars currently on the road, in spawn order.
+    pub fn cars(&self) -> &[Car] {
+        &self.cars
+    }
+
+    /// The 6x6-lane box in the middle of the map.
+    pub fn core_intersection(&self) -> FRect {
+        self.core_intersection
+    }
+
+    /// Act on `input` before the next `step`.
+    pub fn apply(&mut self, input: SimInput) {
+        match input {
+            SimInput::SpawnFrom(approach) => self.spawn_from(approach),
+            SimInput::ToggleRandomGeneration => self.toggle_random_generation(),
+        }
+    }
+
+    /// Whether cars arrive as the scenario's demand says.
+    pub fn is_random_generation(&self) -> bool {
+        self.is_random_generation
+    }
+
+    /// Toggle cars arriving as the scenario's demand says.
+    pub fn toggle_random_generation(&mut self) {
+        self.is_random_generation = !self.is_random_generation;
+        self.demand.restart();
+    }
+
+    /// Cars waiting off the map to enter the lane of `route`.
+    pub fn entry_queue(&self, route: Route) -> usize {
+        self.entry_queues.get(&route).map_or(0, VecDeque::len)
+    }
+
+    /// Cars waiting off the map to enter any lane.
+    pub fn entry_queue_total(&self) -> usize {
+        self.entry_queues.values().map(VecDeque::len).sum()
+    }
+
+    /// Put a car that arrived at the edge of the map on `tick` at the back
+    /// of the entry queue of `route`.
+    fn arrive(&mut self, route: Route, tick: u64) {
+        self.entry_queues.entry(route).or_default().push_back(tick);
+        self.emit(SimEvent::Arrived { tick, route });
+    }
+
+    /// Spawn a car on `route` that arrived on `arrival_tick`, if its spawn
+    /// area is clear. Returns whether the car was spawned.
+    fn spawn(&mut self, route: Route, arrival_tick: u64) -> bool {
+        let spawned = Car::spawn_if_can(
+            &mut self.cars,
+            self.next_id,
+            route,
+            self.tick,
+            &self.scenario,
+            &mut self.rng,
+        );
+        if spawned {
+            self.emit(SimEvent::Spawned {
+                tick: self.tick,
+                car_id: self.next_id,
+                route,
+                arrival_tick,
+            });
+            self.next_id += 1;
+        }
+        spawned
+    }
+
+    /// Queue a car entering from `approach` with a random turn. It arrives
+    /// on the next tick, and drives in then if there's room.
+    pub fn spawn_from(&mut self, approach: Approach) {
+        let turn = Turn::ALL[self.rng.gen_range(0..Turn::ALL.len())];
+        self.arrive(Route::new(approach, turn), self.tick + 1);
+    }
+
+    /// Advance the simulation by one tick of `dt` simulated seconds.
+    /// Front-ends should pass a fixed `TICK_DURATION` so runs stay reproducible.
+    pub fn step(&mut self, dt: f32) {
+        self.tick += 1;
+        self.events.clear();
+
+        // ---------------------------------------
+        // A) New arrivals join the entry queue of their lane, and the
+        //    oldest car of every queue drives in once there's room
+        // ---------------------------------------
+        if self.is_random_generation {
+            let time = self.tick as f32 * TICK_DURATION;
+            let arrivals = self
+                .demand
+                .arrivals(&self.scenario.demand, time, dt, &mut self.rng);
+            for route in arrivals {
+                self.arrive(route, self.tick);
+            }
+        }
+        for route in Route::ALL {
+            let Some(&arrival_tick) = self.entry_queues.get(&route).and_then(VecDeque::front)
+            else {
+                continue;
+            };
+            if self.spawn(route, arrival_tick) {
+                if let Some(queue) = self.entry_queues.get_mut(&route) {
+                    queue.pop_front();
+                }
+            }
+        }
+        // Turn away the latest arrivals of queues that grew too long
+        if let Some(max_queue) = self.scenario.demand.max_queue {
+            for route in Route::ALL {
+                while self.entry_queue(route) > max_queue {
+                    if let Some(queue) = self.entry_queues.get_mut(&route) {
+                        queue.pop_back();
+                    }
+                    self.emit(SimEvent::TurnedAway {
+                        tick: self.tick,
+                        route,
+                    });
+                }
+            }
+        }
+
+        // Ask the policy which approaching cars may enter the intersection
+        {
+            self.policy.begin_tick(self.tick, &self.cars);
+            let temp_cars = self.cars.clone();
+            for car in self.cars.iter_mut() {
+                let lookahead = self.policy.lookahead(car);
+                let held = car.is_approaching(&self.core_intersection, lookahead)
+                    && !self
+                        .policy
+                        .may_enter(car, &temp_cars, &self.core_intersection);
+                car.stop_line_gap =
+                    held.then(|| car.distance_to_stop_line(&self.core_intersection));
+                car.right_of_way = self.policy.has_right_of_way(car);
+            }
+        }
+
+        // ---------------------------------------
+        // B) First pass: Radar & Speed updates
+        // ---------------------------------------
+        {
+            let temp_cars = self.cars.clone();
+            let break_deadlocks = !self.policy.prevents_deadlock();
+            for i in 0..self.cars.len() {
+                // 1) Radar, then let the gap it measured set the speed
+                self.cars[i].update_radar(i, &temp_cars);
+                self.cars[i].adjust_current_speed(dt);
+
+                // 2) Check for perpendicular cars with speed 0 and move one back
+                if break_deadlocks {
+                    for j in (i + 1)..self.cars.len() {
+                        check_perpendicular_and_move_back(&mut self.cars, i, j);
+                    }
+                }
+            }
+        }
+
+        // ---------------------------------------
+        // C) Second pass: Move each car exactly once
+        // ---------------------------------------
+        let mut events = Vec::new();
+        {
+            let tick = self.tick;
+            let mut temp_cars = self.cars.clone();
+            let core = OrientedRect::from(self.core_intersection);
+            for car in self.cars.iter_mut() {
+                let had_turned = car.has_turned;
+                car.move_one_step_if_no_collide(&mut temp_cars, &self.core_intersection, dt);
+                let car_id = car.id;
+
+                // Note the trip milestones
+                if car.current_speed < WAITING_SPEED {
+                    car.wait_ticks += 1;
+                    if car.waiting_since.is_none() {
+                        car.waiting_since = Some(tick);
+                        events.push(SimEvent::StartedWaiting { tick, car_id });
+                    }
+                } else if let Some(since) = car.waiting_since.take() {
+                    let waited = (tick - since) as f32 * TICK_DURATION;
+                    events.push(SimEvent::StoppedWaiting {
+                        tick,
+                        car_id,
+                        waited,
+                    });
+                }
+                if car.entry_tick.is_none() && car.intersection_state == IntersectionState::Inside {
+                    car.entry_tick = Some(tick);
+                    events.push(SimEvent::EnteredIntersection { tick, car_id });
+                } else if car.entry_tick.is_some()
+                    && car.exit_tick.is_none()
+                    && !car.body.intersects(&core)
+                {
+                    car.exit_tick = Some(tick);
+                    events.push(SimEvent::ExitedIntersection { tick, car_id });
+                }
+                if car.has_turned && !had_turned {
+                    events.push(SimEvent::Turned {
+                        tick,
+                        car_id,
+                        route: car.route,
+                    });
+                }
+            }
+        }
+
+        // ---------------------------------------
+        // D) Look for cars that ended up overlapping or nearly did
+        // ---------------------------------------
+        let collisions = self.collision_detector.detect(self.tick, &self.cars);
+        events.extend(collisions.into_iter().map(SimEvent::Collision));
+        let close_calls = self
+            .safety
+            .detect(self.tick, &self.cars, &self.core_intersection);
+        events.extend(close_calls.into_iter().map(SimEvent::CloseCall));
+
+        // ---------------------------------------
+        // E) Remove cars that have reached destination
+        // ---------------------------------------
+        let tick = self.tick;
+        let speeds: Vec<f32> = self.cars.iter().map(|car| car.current_speed).collect();
+        let cars_before = self.cars.len();
+        self.cars.retain(|car| {
+            if car.has_arrived() {
+                events.push(SimEvent::Despawned {
+                    trip: TripRecord::new(car, tick),
+                    comfort: car.comfort.clone(),
+                });
+                false
+            } else {
+                true
+            }
+        });
+        let completed = (cars_before - self.cars.len()) as u32;
+        self.completed += completed;
+
+        // ---------------------------------------
+        // F) Sample the road for the time series
+        // ---------------------------------------
+        let queue = |approach: Approach| {
+            self.cars
+                .iter()
+                .filter(|car| {
+                    car.route.approach() == approach
+                        && car.intersection_state == IntersectionState::Before
+                        && car.current_speed < WAITING_SPEED
+                })
+                .count() as u32
+        };
+        let entry_queue = |approach: Approach| {
+            self.entry_queues
+                .iter()
+                .filter(|(route, _)| route.approach() == approach)
+                .map(|(_, queue)| queue.len() as u32)
+                .sum()
+        };
+        let sample = TickSample {
+            tick: self.tick,
+            time: self.tick as f32 * TICK_DURATION,
+            cars: self.cars.len() as u32,
+            queue_right: queue(Approach::Right),
+            queue_down: queue(Approach::Down),
+            queue_left: queue(Approach::Left),
+            queue_up: queue(Approach::Up),
+            entry_right: entry_queue(Approach::Right),
+            entry_down: entry_queue(Approach::Down),
+            entry_left: entry_queue(Approach::Left),
+            entry_up: entry_queue(Approach::Up),
+            completed,
+            completed_total: self.completed,
+            min_speed: speeds.iter().copied().reduce(f32::min),
+            max_speed: speeds.iter().copied().reduce(f32::max),
+        };
+        events.push(SimEvent::TickEnded(sample));
+
+        for event in events {
+            self.emit(event);
+        }
+    }
+
+    /// Hand `event` to the metrics and every subscriber.
+    fn emit(&mut self, event: SimEvent) {
+        self.metrics.on_event(&event);
+        for subscriber in &mut self.subscribers {
+            subscriber.on_event(&event);
+        }
+        self.events.push(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headless;
+
+    const TICKS: u64 = 1200;
+
+    fn run_alone(seed: u64) -> Metrics {
+        let mut simulation = Simulation::new(seed, Scenario::default());
+        headless::simulate(&mut simulation, TICKS);
+        simulation.metrics().clone()
+    }
+
+    #[test]
+    fn side_by_side_simulations_keep_their_metrics_apart() {
+        let mut first = Simulation::new(1, Scenario::default());
+        let mut second = Simulation::new(2, Scenario::default());
+        first.toggle_random_generation();
+        second.toggle_random_generation();
+        for _ in 0..TICKS {
+            first.step(TICK_DURATION);
+            second.step(TICK_DURATION);
+        }
+
+        assert!(first.metrics().cars_spawned > 0);
+        assert_ne!(first.metrics(), second.metrics());
+        assert_eq!(*first.metrics(), run_alone(1));
+        assert_eq!(*second.metrics(), run_alone(2));
+    }
+
+    #[test]
+    fn same_seed_reruns_match() {
+        let metrics = run_alone(7);
+        assert!(metrics.cars_spawned > 0);
+        assert_eq!(metrics, run_alone(7));
+    }
+}
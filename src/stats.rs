@@ -0,0 +1,178 @@
+/// Mean, median, 95th percentile and maximum of a set of samples.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub mean: f32,
+    pub median: f32,
+    pub p95: f32,
+    pub max: f32,
+}
+
+impl Summary {
+    /// Summarize `samples`, or `None` if there are none.
+    pub fn of(samples: &[f32]) -> Option<Summary> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f32::total_cmp);
+
+        Some(Summary {
+            mean: sorted.iter().sum::<f32>() / sorted.len() as f32,
+            median: percentile(&sorted, 0.5),
+            p95: percentile(&sorted, 0.95),
+            max: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+/// Nearest-rank percentile of non-empty `sorted` samples, `fraction` in 0..=1.
+fn percentile(sorted: &[f32], fraction: f32) -> f32 {
+    let rank = (fraction * sorted.len() as f32).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Mean of a set of samples, with the half-width of its 95% confidence
+/// interval from Student's t-distribution.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    pub mean: f32,
+    /// `None` with a single sample, which says nothing about the spread.
+    pub ci95: Option<f32>,
+}
+
+impl Estimate {
+    /// Estimate the mean behind `samples`, or `None` if there are none.
+    pub fn of(samples: &[f32]) -> Option<Estimate> {
+        if samples.is_empty() {
+            return None;
+        }
+        let n = samples.len() as f32;
+        let mean = samples.iter().sum::<f32>() / n;
+        let ci95 = (samples.len() > 1).then(|| {
+            let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f32>() / (n - 1.0);
+            t_critical(samples.len() - 1) * (variance / n).sqrt()
+        });
+        Some(Estimate { mean, ci95 })
+    }
+}
+
+/// Two-sided 95% critical value of Student's t-distribution with
+/// `degrees_of_freedom`, rounded down to the nearest tabulated one.
+fn t_critical(degrees_of_freedom: usize) -> f32 {
+    const TABLE: [f32; 30] = [
+        12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+        2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+        2.052, 2.048, 2.045, 2.042,
+    ];
+    match degrees_of_freedom {
+        0 => f32::INFINITY,
+        1..=30 => TABLE[degrees_of_freedom - 1],
+        31..=39 => 2.042,
+        40..=59 => 2.021,
+        60..=119 => 2.000,
+        120..=999 => 1.980,
+        _ => 1.960,
+    }
+}
+
+/// One bar of a histogram: samples in `start..end`, the last bar including `end`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bin {
+    pub start: f32,
+    pub end: f32,
+    pub count: usize,
+}
+
+/// Split the range of `samples` into `bins` bars of equal width.
+/// Empty if there are no samples.
+pub fn histogram(samples: &[f32], bins: usize) -> Vec<Bin> {
+    let Some(min) = samples.iter().copied().reduce(f32::min) else {
+        return Vec::new();
+    };
+    let max = samples.iter().copied().fold(min, f32::max);
+    let bins = bins.max(1);
+    let width = ((max - min) / bins as f32).max(f32::EPSILON);
+
+    let mut histogram: Vec<Bin> = (0..bins)
+        .map(|i| Bin {
+            start: min + i as f32 * width,
+            end: min + (i + 1) as f32 * width,
+            count: 0,
+        })
+        .collect();
+    for &sample in samples {
+        let i = (((sample - min) / width) as usize).min(bins - 1);
+        histogram[i].count += 1;
+    }
+    histogram
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(actual: f32, expected: f32) -> bool {
+        (actual - expected).abs() < 1e-3
+    }
+
+    #[test]
+    fn estimate_of_a_single_sample_has_no_interval() {
+        assert_eq!(Estimate::of(&[]), None);
+        assert_eq!(
+            Estimate::of(&[4.0]),
+            Some(Estimate {
+                mean: 4.0,
+                ci95: None
+            })
+        );
+    }
+
+    #[test]
+    fn estimate_of_two_samples() {
+        // Standard error 1, with t = 12.706 for one degree of freedom
+        let estimate = Estimate::of(&[1.0, 3.0]).unwrap();
+        assert_eq!(estimate.mean, 2.0);
+        assert!(close(estimate.ci95.unwrap(), 12.706));
+    }
+
+    #[test]
+    fn estimate_of_31_samples() {
+        // 0 to 30: variance 2480 / 30, standard error sqrt(2480 / 30 / 31)
+        let samples: Vec<f32> = (0..=30).map(|i| i as f32).collect();
+        let estimate = Estimate::of(&samples).unwrap();
+        assert_eq!(estimate.mean, 15.0);
+        assert!(close(
+            estimate.ci95.unwrap(),
+            2.042 * (2480.0f32 / 30.0 / 31.0).sqrt()
+        ));
+    }
+
+    #[test]
+    fn t_critical_matches_the_table() {
+        assert_eq!(t_critical(0), f32::INFINITY);
+        assert_eq!(t_critical(1), 12.706);
+        assert_eq!(t_critical(2), 4.303);
+        assert_eq!(t_critical(30), 2.042);
+        assert_eq!(t_critical(31), 2.042);
+        assert_eq!(t_critical(40), 2.021);
+        assert_eq!(t_critical(60), 2.000);
+        assert_eq!(t_critical(120), 1.980);
+        assert_eq!(t_critical(1000), 1.960);
+        // Never grows with more degrees of freedom
+        assert!((1..2000).all(|df| t_critical(df + 1) <= t_critical(df)));
+    }
+
+    #[test]
+    fn percentile_uses_the_nearest_rank() {
+        assert_eq!(percentile(&[7.0], 0.0), 7.0);
+        assert_eq!(percentile(&[7.0], 0.95), 7.0);
+        assert_eq!(percentile(&[7.0], 1.0), 7.0);
+
+        let sorted: Vec<f32> = (1..=20).map(|i| i as f32).collect();
+        assert_eq!(percentile(&sorted, 0.0), 1.0);
+        assert_eq!(percentile(&sorted, 0.5), 10.0);
+        assert_eq!(percentile(&sorted, 0.95), 19.0);
+        assert_eq!(percentile(&sorted, 1.0), 20.0);
+        assert_eq!(percentile(&[1.0, 2.0], 0.5), 1.0);
+    }
+}
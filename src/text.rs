@@ -4,6 +4,7 @@ use sdl2::render::TextureCreator;
 use sdl2::ttf::Sdl2TtfContext;
 use sdl2::video::WindowContext;
 
+#[allow(clippy::too_many_arguments)]
 pub fn draw_text<'a>(
     texture_creator: &'a TextureCreator<WindowContext>,
     font_path: &str,
@@ -0,0 +1,58 @@
+use crate::car::Car;
+use crate::constants::*;
+use crate::route::Route;
+use serde::Serialize;
+
+/// Below this speed a car counts as waiting, in px/s.
+pub const WAITING_SPEED: f32 = 1.0;
+
+/// How one car's journey went, from spawning to leaving the map.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TripRecord {
+    pub car_id: u32,
+    pub route: Route,
+    pub spawn_tick: u64,
+    /// Tick the car first touched the core intersection.
+    pub entry_tick: Option<u64>,
+    /// Tick the car was last clear of the core intersection after entering it.
+    pub exit_tick: Option<u64>,
+    pub despawn_tick: u64,
+    /// Seconds spent below `WAITING_SPEED`.
+    pub wait_time: f32,
+    /// Distance driven over trip time, in px/s.
+    pub average_speed: f32,
+}
+
+impl TripRecord {
+    /// Record of `car`, which leaves the map on `despawn_tick`.
+    pub fn new(car: &Car, despawn_tick: u64) -> Self {
+        let trip_time = (despawn_tick - car.spawn_tick) as f32 * TICK_DURATION;
+        Self {
+            car_id: car.id,
+            route: car.route,
+            spawn_tick: car.spawn_tick,
+            entry_tick: car.entry_tick,
+            exit_tick: car.exit_tick,
+            despawn_tick,
+            wait_time: car.wait_ticks as f32 * TICK_DURATION,
+            average_speed: if trip_time > 0.0 {
+                car.distance / trip_time
+            } else {
+                0.0
+            },
+        }
+    }
+
+    /// Seconds from spawning to leaving the map.
+    pub fn trip_time(&self) -> f32 {
+        (self.despawn_tick - self.spawn_tick) as f32 * TICK_DURATION
+    }
+
+    /// Seconds spent inside the core intersection, if the car crossed it.
+    pub fn crossing_time(&self) -> Option<f32> {
+        match (self.entry_tick, self.exit_tick) {
+            (Some(entry), Some(exit)) => Some((exit - entry) as f32 * TICK_DURATION),
+            _ => None,
+        }
+    }
+}